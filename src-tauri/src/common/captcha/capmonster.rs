@@ -0,0 +1,148 @@
+use super::task_api::{proxy_fields, TaskApi};
+use super::types::*;
+use serde_json::{json, Map, Value};
+
+/// CapMonster Cloud（任务类型不区分是否带代理）
+pub struct CapMonster {
+    api: TaskApi,
+}
+
+impl CapMonster {
+    pub fn new(config: &CaptchaProviderConfig, settings: &CaptchaSettings) -> Result<Self, CaptchaError> {
+        Ok(Self {
+            api: TaskApi::new(config, settings)?,
+        })
+    }
+
+    fn build_task(&self, task: &CaptchaTask) -> Result<Map<String, Value>, CaptchaError> {
+        let mut payload = Map::new();
+        if !task.website_url.is_empty() {
+            payload.insert("websiteURL".into(), json!(task.website_url));
+        }
+
+        let uses_proxy = match &task.kind {
+            CaptchaKind::Turnstile {
+                website_key,
+                action,
+                cdata,
+            } => {
+                payload.insert("type".into(), json!("TurnstileTask"));
+                payload.insert("websiteKey".into(), json!(website_key));
+                if let Some(action) = action {
+                    payload.insert("pageAction".into(), json!(action));
+                }
+                if let Some(cdata) = cdata {
+                    payload.insert("data".into(), json!(cdata));
+                }
+                true
+            }
+            CaptchaKind::RecaptchaV2 {
+                website_key,
+                data_s,
+                enterprise,
+                enterprise_payload,
+                api_domain,
+                ..
+            } => {
+                let task_type = if *enterprise {
+                    "RecaptchaV2EnterpriseTask"
+                } else {
+                    "RecaptchaV2Task"
+                };
+                payload.insert("type".into(), json!(task_type));
+                payload.insert("websiteKey".into(), json!(website_key));
+                if let Some(s) = data_s {
+                    payload.insert("recaptchaDataSValue".into(), json!(s));
+                }
+                if let (true, Some(p)) = (*enterprise, enterprise_payload) {
+                    payload.insert("enterprisePayload".into(), p.clone());
+                }
+                if let Some(domain) = api_domain {
+                    payload.insert("apiDomain".into(), json!(domain));
+                }
+                true
+            }
+            CaptchaKind::RecaptchaV3 {
+                website_key,
+                action,
+                min_score,
+                enterprise,
+                ..
+            } => {
+                let task_type = if *enterprise {
+                    "RecaptchaV3EnterpriseTask"
+                } else {
+                    "RecaptchaV3TaskProxyless"
+                };
+                payload.insert("type".into(), json!(task_type));
+                payload.insert("websiteKey".into(), json!(website_key));
+                payload.insert("pageAction".into(), json!(action));
+                if let Some(score) = min_score {
+                    payload.insert("minScore".into(), json!(score));
+                }
+                false
+            }
+            CaptchaKind::HCaptcha {
+                website_key,
+                invisible,
+                enterprise_payload,
+            } => {
+                payload.insert("type".into(), json!("HCaptchaTask"));
+                payload.insert("websiteKey".into(), json!(website_key));
+                payload.insert("isInvisible".into(), json!(invisible));
+                // Enterprise 的 rqdata 以 data 字段传入
+                if let Some(rqdata) = enterprise_payload.as_ref().and_then(|p| p.get("rqdata")) {
+                    payload.insert("data".into(), rqdata.clone());
+                }
+                true
+            }
+            CaptchaKind::GeeTestV4 {
+                captcha_id,
+                api_server_subdomain,
+                init_parameters,
+            } => {
+                payload.insert("type".into(), json!("GeeTestTask"));
+                payload.insert("gt".into(), json!(captcha_id));
+                payload.insert("version".into(), json!(4));
+                if let Some(p) = init_parameters {
+                    payload.insert("initParameters".into(), p.clone());
+                }
+                if let Some(subdomain) = api_server_subdomain {
+                    payload.insert("geetestApiServerSubdomain".into(), json!(subdomain));
+                }
+                true
+            }
+            CaptchaKind::Image {
+                body,
+                case_sensitive,
+                numeric,
+                ..
+            } => {
+                payload.insert("type".into(), json!("ImageToTextTask"));
+                payload.insert("body".into(), json!(body));
+                payload.insert("Case".into(), json!(case_sensitive));
+                payload.insert("numeric".into(), json!(if *numeric { 1 } else { 0 }));
+                false
+            }
+        };
+
+        if let (Some(proxy), true) = (&task.proxy, uses_proxy) {
+            payload.extend(proxy_fields(self.provider(), proxy)?);
+        }
+        Ok(payload)
+    }
+}
+
+impl CaptchaSolver for CapMonster {
+    fn provider(&self) -> CaptchaProvider {
+        CaptchaProvider::CapMonster
+    }
+
+    fn solve<'a>(&'a self, task: &'a CaptchaTask) -> SolveFuture<'a, CaptchaSolution> {
+        Box::pin(async move { self.api.solve(task, self.build_task(task)?).await })
+    }
+
+    fn balance(&self) -> SolveFuture<'_, f64> {
+        Box::pin(self.api.balance())
+    }
+}
@@ -0,0 +1,147 @@
+use super::task_api::{unsupported, TaskApi};
+use super::types::*;
+use serde_json::{json, Map, Value};
+
+/// CapSolver（代理以完整URL字符串传入，不支持 hCaptcha）
+pub struct CapSolver {
+    api: TaskApi,
+}
+
+impl CapSolver {
+    pub fn new(config: &CaptchaProviderConfig, settings: &CaptchaSettings) -> Result<Self, CaptchaError> {
+        Ok(Self {
+            api: TaskApi::new(config, settings)?,
+        })
+    }
+
+    fn build_task(&self, task: &CaptchaTask) -> Result<Map<String, Value>, CaptchaError> {
+        let mut payload = Map::new();
+        if !task.website_url.is_empty() {
+            payload.insert("websiteURL".into(), json!(task.website_url));
+        }
+
+        let proxied = task.proxy.is_some();
+        let task_type = |base: &str| {
+            if proxied {
+                base.to_string()
+            } else {
+                format!("{}ProxyLess", base)
+            }
+        };
+
+        let uses_proxy = match &task.kind {
+            CaptchaKind::Turnstile {
+                website_key,
+                action,
+                cdata,
+            } => {
+                payload.insert("type".into(), json!(task_type("AntiTurnstileTask")));
+                payload.insert("websiteKey".into(), json!(website_key));
+
+                let mut metadata = Map::new();
+                if let Some(action) = action {
+                    metadata.insert("action".into(), json!(action));
+                }
+                if let Some(cdata) = cdata {
+                    metadata.insert("cdata".into(), json!(cdata));
+                }
+                if !metadata.is_empty() {
+                    payload.insert("metadata".into(), Value::Object(metadata));
+                }
+                true
+            }
+            CaptchaKind::RecaptchaV2 {
+                website_key,
+                invisible,
+                data_s,
+                enterprise,
+                enterprise_payload,
+                api_domain,
+            } => {
+                let base = if *enterprise {
+                    "ReCaptchaV2EnterpriseTask"
+                } else {
+                    "ReCaptchaV2Task"
+                };
+                payload.insert("type".into(), json!(task_type(base)));
+                payload.insert("websiteKey".into(), json!(website_key));
+                if *invisible {
+                    payload.insert("isInvisible".into(), json!(true));
+                }
+
+                // data-s 通过 enterprisePayload.s 传入
+                let mut extra = match enterprise_payload {
+                    Some(Value::Object(p)) => p.clone(),
+                    _ => Map::new(),
+                };
+                if let Some(s) = data_s {
+                    extra.insert("s".into(), json!(s));
+                }
+                if !extra.is_empty() {
+                    payload.insert("enterprisePayload".into(), Value::Object(extra));
+                }
+                if let Some(domain) = api_domain {
+                    payload.insert("apiDomain".into(), json!(domain));
+                }
+                true
+            }
+            CaptchaKind::RecaptchaV3 {
+                website_key,
+                action,
+                enterprise,
+                api_domain,
+                ..
+            } => {
+                let base = if *enterprise {
+                    "ReCaptchaV3EnterpriseTask"
+                } else {
+                    "ReCaptchaV3Task"
+                };
+                payload.insert("type".into(), json!(task_type(base)));
+                payload.insert("websiteKey".into(), json!(website_key));
+                payload.insert("pageAction".into(), json!(action));
+                if let Some(domain) = api_domain {
+                    payload.insert("apiDomain".into(), json!(domain));
+                }
+                true
+            }
+            CaptchaKind::HCaptcha { .. } => return Err(unsupported(self.provider(), &task.kind)),
+            CaptchaKind::GeeTestV4 {
+                captcha_id,
+                api_server_subdomain,
+                ..
+            } => {
+                payload.insert("type".into(), json!(task_type("GeeTestTask")));
+                payload.insert("captchaId".into(), json!(captcha_id));
+                if let Some(subdomain) = api_server_subdomain {
+                    payload.insert("geetestApiServerSubdomain".into(), json!(subdomain));
+                }
+                true
+            }
+            CaptchaKind::Image { body, .. } => {
+                payload.insert("type".into(), json!("ImageToTextTask"));
+                payload.insert("body".into(), json!(body));
+                false
+            }
+        };
+
+        if let (Some(proxy), true) = (&task.proxy, uses_proxy) {
+            payload.insert("proxy".into(), json!(proxy));
+        }
+        Ok(payload)
+    }
+}
+
+impl CaptchaSolver for CapSolver {
+    fn provider(&self) -> CaptchaProvider {
+        CaptchaProvider::CapSolver
+    }
+
+    fn solve<'a>(&'a self, task: &'a CaptchaTask) -> SolveFuture<'a, CaptchaSolution> {
+        Box::pin(async move { self.api.solve(task, self.build_task(task)?).await })
+    }
+
+    fn balance(&self) -> SolveFuture<'_, f64> {
+        Box::pin(self.api.balance())
+    }
+}
@@ -0,0 +1,14 @@
+pub mod types;
+mod task_api;
+pub mod capsolver;
+pub mod two_captcha;
+pub mod anti_captcha;
+pub mod capmonster;
+pub mod service;
+pub mod usage;
+pub mod pool;
+
+pub use types::*;
+pub use service::*;
+pub use usage::*;
+pub use pool::*;
@@ -0,0 +1,338 @@
+use super::service::CaptchaService;
+use super::types::*;
+use super::usage::CaptchaUsageTags;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// 令牌到期前预留的提交时间
+const EXPIRY_MARGIN: Duration = Duration::from_secs(15);
+/// 统计需求速率的时间窗口
+const DEMAND_WINDOW: Duration = Duration::from_secs(300);
+/// 后台补充检查间隔（替换即将过期的令牌）
+const REFILL_TICK: Duration = Duration::from_secs(5);
+/// 解题失败后再次补充前的等待，避免连续失败时空转
+const FAILURE_BACKOFF: Duration = Duration::from_secs(5);
+/// worker 等待令牌的最长时间
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(300);
+/// 尚无延迟数据时假定的解题耗时
+const DEFAULT_LATENCY_MS: f64 = 30_000.0;
+
+/// 预解池配置
+#[derive(Debug, Clone)]
+pub struct TokenPoolConfig {
+    pub task: CaptchaTask,
+    /// 令牌有效期，None 按验证码类型取默认值
+    pub ttl: Option<Duration>,
+    /// 脚本并发数（同时可能来取令牌的 worker 数）
+    pub concurrency: usize,
+    /// 池中最多持有的令牌数（含解题中）
+    pub max_size: usize,
+}
+
+/// 各类型令牌的默认有效期，图片识别无法预解返回 None
+fn default_ttl(kind: &CaptchaKind) -> Option<Duration> {
+    let secs = match kind {
+        CaptchaKind::Turnstile { .. } => 300,
+        CaptchaKind::RecaptchaV2 { .. } | CaptchaKind::RecaptchaV3 { .. } => 120,
+        CaptchaKind::HCaptcha { .. } => 120,
+        CaptchaKind::GeeTestV4 { .. } => 60,
+        CaptchaKind::Image { .. } => return None,
+    };
+    Some(Duration::from_secs(secs))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenPoolStats {
+    pub ready: usize,
+    pub in_flight: usize,
+    pub waiting: usize,
+    pub target: usize,
+    pub avg_latency_ms: Option<u64>,
+    pub demand_per_min: f64,
+    pub solved: u64,
+    pub expired: u64,
+    pub failed: u64,
+}
+
+struct PooledToken {
+    solution: CaptchaSolution,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct PoolState {
+    ready: VecDeque<PooledToken>,
+    in_flight: usize,
+    waiting: usize,
+    /// 解题耗时的指数移动平均
+    latency_ms: Option<f64>,
+    /// 窗口内每次取令牌的时间
+    demand: VecDeque<Instant>,
+    ever_demanded: bool,
+    solved: u64,
+    expired: u64,
+    failed: u64,
+    /// 解题失败后在此之前不再补充
+    backoff_until: Option<Instant>,
+    /// 预算耗尽等不可恢复的错误，等待中的 worker 直接返回
+    fatal: Option<CaptchaError>,
+}
+
+struct PoolInner {
+    service: CaptchaService,
+    task: CaptchaTask,
+    usable_ttl: Duration,
+    concurrency: usize,
+    max_size: usize,
+    tags: CaptchaUsageTags,
+    state: Mutex<PoolState>,
+    token_ready: Notify,
+    wake: Notify,
+    stopped: AtomicBool,
+}
+
+impl PoolInner {
+    fn purge(&self, state: &mut PoolState) {
+        let now = Instant::now();
+        let before = state.ready.len();
+        state.ready.retain(|t| t.expires_at > now);
+        state.expired += (before - state.ready.len()) as u64;
+
+        while state
+            .demand
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > DEMAND_WINDOW)
+        {
+            state.demand.pop_front();
+        }
+    }
+
+    /// 每秒需求数
+    fn demand_rate(&self, state: &PoolState) -> f64 {
+        let oldest = match state.demand.front() {
+            Some(t) => *t,
+            None => return 0.0,
+        };
+        // 窗口未满时按实际时长计算，至少 30 秒避免刚开始时估计过高
+        let span = oldest.elapsed().max(Duration::from_secs(30)).min(DEMAND_WINDOW);
+        state.demand.len() as f64 / span.as_secs_f64()
+    }
+
+    /// 期望持有的令牌数（含解题中）
+    ///
+    /// 覆盖一个解题周期内的需求，但不超过有效期内用得完的数量；
+    /// 尚无需求数据时按并发数预热，需求停止后不再补充
+    fn target(&self, state: &PoolState) -> usize {
+        let max = self.max_size.max(1);
+        if state.demand.is_empty() {
+            return if state.ever_demanded {
+                state.waiting.min(max)
+            } else {
+                self.concurrency.clamp(1, max)
+            };
+        }
+
+        let rate = self.demand_rate(state);
+        let latency = state.latency_ms.unwrap_or(DEFAULT_LATENCY_MS) / 1000.0;
+        let by_latency = (rate * latency * 1.2).ceil() as usize + state.waiting;
+        let by_ttl = (rate * self.usable_ttl.as_secs_f64()).ceil() as usize + state.waiting;
+
+        by_latency.max(1).min(by_ttl.max(1)).min(max)
+    }
+
+    /// 按目标数量补充解题任务
+    fn refill(self: &Arc<Self>) {
+        if self.stopped.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let deficit = {
+            let mut state = self.state.lock().unwrap();
+            if state.fatal.is_some() || state.backoff_until.is_some_and(|t| t > Instant::now()) {
+                return;
+            }
+            self.purge(&mut state);
+            let have = state.ready.len() + state.in_flight;
+            let deficit = self.target(&state).saturating_sub(have);
+            state.in_flight += deficit;
+            deficit
+        };
+
+        for _ in 0..deficit {
+            let inner = self.clone();
+            tokio::spawn(async move { inner.solve_one().await });
+        }
+    }
+
+    async fn solve_one(self: Arc<Self>) {
+        let result = self.service.solve(&self.task, &self.tags).await;
+        let failed = {
+            let mut state = self.state.lock().unwrap();
+            state.in_flight -= 1;
+
+            match result {
+                Ok(solution) => {
+                    let sample = solution.elapsed_ms as f64;
+                    state.latency_ms = Some(match state.latency_ms {
+                        Some(avg) => avg * 0.7 + sample * 0.3,
+                        None => sample,
+                    });
+                    state.solved += 1;
+                    if !self.stopped.load(Ordering::Relaxed) {
+                        state.ready.push_back(PooledToken {
+                            solution,
+                            expires_at: Instant::now() + self.usable_ttl,
+                        });
+                    }
+                    false
+                }
+                Err(e) => {
+                    state.failed += 1;
+                    // 服务内部已重试和切换平台，仍非临时错误说明继续解题也无用
+                    if e.is_retryable() {
+                        state.backoff_until = Some(Instant::now() + FAILURE_BACKOFF);
+                    } else {
+                        state.fatal = Some(e);
+                    }
+                    true
+                }
+            }
+        };
+
+        if failed {
+            self.token_ready.notify_waiters();
+        } else {
+            self.token_ready.notify_one();
+            self.wake.notify_one();
+        }
+    }
+}
+
+/// 验证码预解池：后台提前解好令牌，worker 取用时直接拿到未过期的
+///
+/// 克隆共享同一个池；不再使用时调用 `stop`
+#[derive(Clone)]
+pub struct TokenPool {
+    inner: Arc<PoolInner>,
+}
+
+impl TokenPool {
+    /// 创建并启动预解池（需在 tokio 运行时中调用）
+    pub fn start(
+        service: CaptchaService,
+        config: TokenPoolConfig,
+        tags: CaptchaUsageTags,
+    ) -> Result<Self, CaptchaError> {
+        let ttl = default_ttl(&config.task.kind)
+            .map(|default| config.ttl.unwrap_or(default))
+            .ok_or_else(|| {
+                CaptchaError::new(
+                    CaptchaErrorKind::InvalidTask,
+                    None,
+                    "Image captchas cannot be pre-solved",
+                )
+            })?;
+        let inner = Arc::new(PoolInner {
+            service,
+            usable_ttl: ttl.saturating_sub(EXPIRY_MARGIN).max(Duration::from_secs(5)),
+            task: config.task,
+            concurrency: config.concurrency,
+            max_size: config.max_size,
+            tags,
+            state: Mutex::new(PoolState::default()),
+            token_ready: Notify::new(),
+            wake: Notify::new(),
+            stopped: AtomicBool::new(false),
+        });
+
+        let worker = inner.clone();
+        tokio::spawn(async move {
+            while !worker.stopped.load(Ordering::Relaxed) {
+                worker.refill();
+                tokio::select! {
+                    _ = worker.wake.notified() => {}
+                    _ = tokio::time::sleep(REFILL_TICK) => {}
+                }
+            }
+        });
+
+        Ok(Self { inner })
+    }
+
+    /// 取一个未过期的令牌，池空时等待解题完成
+    pub async fn acquire(&self) -> Result<CaptchaSolution, CaptchaError> {
+        let deadline = Instant::now() + ACQUIRE_TIMEOUT;
+        {
+            let mut state = self.inner.state.lock().unwrap();
+            state.demand.push_back(Instant::now());
+            state.ever_demanded = true;
+        }
+
+        loop {
+            // 先注册通知再检查，避免错过检查与等待之间放入的令牌
+            let notified = self.inner.token_ready.notified();
+            {
+                let mut state = self.inner.state.lock().unwrap();
+                self.inner.purge(&mut state);
+                if let Some(token) = state.ready.pop_front() {
+                    drop(state);
+                    self.inner.wake.notify_one();
+                    return Ok(token.solution);
+                }
+                if let Some(e) = &state.fatal {
+                    return Err(e.clone());
+                }
+                if self.inner.stopped.load(Ordering::Relaxed) {
+                    return Err(CaptchaError::new(
+                        CaptchaErrorKind::NotConfigured,
+                        None,
+                        "Captcha token pool stopped",
+                    ));
+                }
+                state.waiting += 1;
+            }
+            self.inner.refill();
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let timed_out = tokio::time::timeout(remaining, notified).await.is_err();
+            self.inner.state.lock().unwrap().waiting -= 1;
+
+            if timed_out {
+                return Err(CaptchaError::new(
+                    CaptchaErrorKind::Transient,
+                    None,
+                    "Timed out waiting for a pooled captcha token",
+                ));
+            }
+        }
+    }
+
+    pub fn stats(&self) -> TokenPoolStats {
+        let mut state = self.inner.state.lock().unwrap();
+        self.inner.purge(&mut state);
+
+        TokenPoolStats {
+            ready: state.ready.len(),
+            in_flight: state.in_flight,
+            waiting: state.waiting,
+            target: self.inner.target(&state),
+            avg_latency_ms: state.latency_ms.map(|l| l as u64),
+            demand_per_min: self.inner.demand_rate(&state) * 60.0,
+            solved: state.solved,
+            expired: state.expired,
+            failed: state.failed,
+        }
+    }
+
+    /// 停止补充并丢弃剩余令牌（解题中的任务完成后丢弃）
+    pub fn stop(&self) {
+        self.inner.stopped.store(true, Ordering::Relaxed);
+        self.inner.state.lock().unwrap().ready.clear();
+        self.inner.wake.notify_one();
+        self.inner.token_ready.notify_waiters();
+    }
+}
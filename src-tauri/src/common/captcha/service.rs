@@ -0,0 +1,120 @@
+use super::anti_captcha::AntiCaptcha;
+use super::capmonster::CapMonster;
+use super::capsolver::CapSolver;
+use super::two_captcha::TwoCaptcha;
+use super::types::*;
+use super::usage::{self, CaptchaSolveRecord, CaptchaUsageTags};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 临时错误后重试同一平台前的等待
+const RETRY_DELAY: Duration = Duration::from_secs(3);
+
+/// 按配置创建单个平台的实现
+pub fn create_solver(
+    config: &CaptchaProviderConfig,
+    settings: &CaptchaSettings,
+) -> Result<Arc<dyn CaptchaSolver>, CaptchaError> {
+    Ok(match config.provider {
+        CaptchaProvider::CapSolver => Arc::new(CapSolver::new(config, settings)?),
+        CaptchaProvider::TwoCaptcha => Arc::new(TwoCaptcha::new(config, settings)?),
+        CaptchaProvider::AntiCaptcha => Arc::new(AntiCaptcha::new(config, settings)?),
+        CaptchaProvider::CapMonster => Arc::new(CapMonster::new(config, settings)?),
+    })
+}
+
+/// 打码服务：按设置顺序使用各平台，临时错误重试，账户错误换平台
+#[derive(Clone)]
+pub struct CaptchaService {
+    solvers: Vec<Arc<dyn CaptchaSolver>>,
+    attempts_per_provider: u32,
+    daily_budget: Option<f64>,
+    run_budget: Option<f64>,
+}
+
+impl CaptchaService {
+    pub fn from_settings(settings: &CaptchaSettings) -> Result<Self, CaptchaError> {
+        let solvers = settings
+            .providers
+            .iter()
+            .filter(|p| p.enabled && !p.api_key.trim().is_empty())
+            .map(|p| create_solver(p, settings))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if solvers.is_empty() {
+            return Err(CaptchaError::new(
+                CaptchaErrorKind::NotConfigured,
+                None,
+                "No captcha provider configured",
+            ));
+        }
+
+        Ok(Self {
+            solvers,
+            attempts_per_provider: settings.attempts_per_provider.max(1),
+            daily_budget: settings.daily_budget,
+            run_budget: settings.run_budget,
+        })
+    }
+
+    pub fn providers(&self) -> Vec<CaptchaProvider> {
+        self.solvers.iter().map(|s| s.provider()).collect()
+    }
+
+    /// 各平台余额
+    pub async fn balances(&self) -> Vec<(CaptchaProvider, Result<f64, CaptchaError>)> {
+        let mut balances = Vec::new();
+        for solver in &self.solvers {
+            balances.push((solver.provider(), solver.balance().await));
+        }
+        balances
+    }
+
+    /// 打码，每次尝试都会记录花费；超出预算时不再提交任务
+    pub async fn solve(
+        &self,
+        task: &CaptchaTask,
+        tags: &CaptchaUsageTags,
+    ) -> Result<CaptchaSolution, CaptchaError> {
+        let mut last_error = None;
+
+        for solver in &self.solvers {
+            for attempt in 1..=self.attempts_per_provider {
+                usage::check_budget(tags, self.daily_budget, self.run_budget)?;
+
+                let started = Instant::now();
+                let result = solver.solve(task).await;
+                usage::record(CaptchaSolveRecord {
+                    solved_at: chrono::Utc::now().to_rfc3339(),
+                    provider: solver.provider(),
+                    kind: task.kind.name().to_string(),
+                    duration_ms: started.elapsed().as_millis() as u64,
+                    cost: result.as_ref().ok().and_then(|s| s.cost).unwrap_or(0.0),
+                    success: result.is_ok(),
+                    error: result.as_ref().err().map(|e| e.to_string()),
+                    script: tags.script.clone(),
+                    wallet: tags.wallet.clone(),
+                    run_id: tags.run_id.clone(),
+                });
+
+                let error = match result {
+                    Ok(solution) => return Ok(solution),
+                    Err(e) => e,
+                };
+
+                if !error.should_fall_over() {
+                    return Err(error);
+                }
+
+                let retry = error.is_retryable() && attempt < self.attempts_per_provider;
+                last_error = Some(error);
+                if !retry {
+                    break;
+                }
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        }
+
+        Err(last_error.expect("at least one solver"))
+    }
+}
@@ -0,0 +1,620 @@
+//! createTask / getTaskResult 协议（CapSolver、2Captcha v2、Anti-Captcha、CapMonster 通用）
+
+use super::types::*;
+use crate::common::http::{create_client, HttpClient, RequestTags};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiReply {
+    #[serde(default)]
+    error_id: i64,
+    error_code: Option<String>,
+    error_description: Option<String>,
+    /// CapSolver 为字符串，其他平台为数字
+    task_id: Option<Value>,
+    status: Option<String>,
+    solution: Option<Value>,
+    /// 2Captcha / Anti-Captcha 在结果中返回本次费用（字符串或数字）
+    cost: Option<Value>,
+    balance: Option<Value>,
+}
+
+fn value_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// 一个打码平台的接口连接
+pub(super) struct TaskApi {
+    provider: CaptchaProvider,
+    base_url: String,
+    api_key: String,
+    http: HttpClient,
+    poll_interval: Duration,
+    timeout: Duration,
+    prices: HashMap<String, f64>,
+}
+
+impl TaskApi {
+    pub fn new(config: &CaptchaProviderConfig, settings: &CaptchaSettings) -> Result<Self, CaptchaError> {
+        let client = create_client(None)
+            .map_err(|e| CaptchaError::new(CaptchaErrorKind::Network, Some(config.provider), e))?;
+        let base_url = config
+            .base_url
+            .as_deref()
+            .filter(|u| !u.is_empty())
+            .unwrap_or(config.provider.default_base_url())
+            .trim_end_matches('/')
+            .to_string();
+
+        Ok(Self {
+            provider: config.provider,
+            base_url,
+            api_key: config.api_key.clone(),
+            http: HttpClient::new(client, RequestTags::default()),
+            poll_interval: Duration::from_millis(settings.poll_interval_ms.max(500)),
+            timeout: Duration::from_secs(settings.timeout_secs.max(10)),
+            prices: config.prices.clone(),
+        })
+    }
+
+    fn error(&self, kind: CaptchaErrorKind, message: impl Into<String>) -> CaptchaError {
+        CaptchaError::new(kind, Some(self.provider), message)
+    }
+
+    async fn call(&self, method: &str, mut body: Map<String, Value>) -> Result<ApiReply, CaptchaError> {
+        body.insert("clientKey".into(), json!(self.api_key));
+
+        let request = self
+            .http
+            .post(&format!("{}/{}", self.base_url, method))
+            .json(&body);
+        // 查询结果/余额可以重发；重复 createTask 会多扣费，只在未发出时重试
+        let response = if method == "createTask" {
+            self.http.send(request).await
+        } else {
+            self.http.send_idempotent(request).await
+        }
+        .map_err(|e| self.error(CaptchaErrorKind::Network, e.to_string()))?;
+        let reply: ApiReply = response
+            .json()
+            .map_err(|e| self.error(CaptchaErrorKind::Network, e.to_string()))?;
+
+        if reply.error_id != 0 {
+            let code = reply.error_code.clone().unwrap_or_default();
+            return Err(CaptchaError {
+                kind: classify_error(&code),
+                provider: Some(self.provider),
+                message: reply
+                    .error_description
+                    .clone()
+                    .unwrap_or_else(|| "unknown error".to_string()),
+                code: Some(code),
+            });
+        }
+
+        Ok(reply)
+    }
+
+    /// 提交任务，返回任务ID
+    pub async fn create_task(&self, task: Map<String, Value>) -> Result<String, CaptchaError> {
+        let mut body = Map::new();
+        body.insert("task".into(), Value::Object(task));
+
+        let reply = self.call("createTask", body).await?;
+        match reply.task_id {
+            Some(Value::String(id)) => Ok(id),
+            Some(Value::Number(id)) => Ok(id.to_string()),
+            _ => Err(self.error(CaptchaErrorKind::Network, "No taskId returned")),
+        }
+    }
+
+    /// 账户余额
+    pub async fn balance(&self) -> Result<f64, CaptchaError> {
+        let reply = self.call("getBalance", Map::new()).await?;
+        reply
+            .balance
+            .as_ref()
+            .and_then(value_f64)
+            .ok_or_else(|| self.error(CaptchaErrorKind::Network, "No balance returned"))
+    }
+
+    /// 轮询任务结果直至完成或超时，返回 (solution 对象, 平台返回的费用)
+    pub async fn wait_result(
+        &self,
+        task_id: &str,
+    ) -> Result<(Map<String, Value>, Option<f64>), CaptchaError> {
+        // CapSolver 要求字符串，其他平台要求数字
+        let id_value = match task_id.parse::<u64>() {
+            Ok(n) if self.provider != CaptchaProvider::CapSolver => json!(n),
+            _ => json!(task_id),
+        };
+
+        let started = Instant::now();
+        loop {
+            if started.elapsed() > self.timeout {
+                return Err(self.error(CaptchaErrorKind::Transient, "Timeout waiting for solution"));
+            }
+            tokio::time::sleep(self.poll_interval).await;
+
+            let mut body = Map::new();
+            body.insert("taskId".into(), id_value.clone());
+            let reply = self.call("getTaskResult", body).await?;
+
+            match reply.status.as_deref() {
+                Some("ready") => {
+                    let cost = reply.cost.as_ref().and_then(value_f64);
+                    return match reply.solution {
+                        Some(Value::Object(solution)) => Ok((solution, cost)),
+                        _ => Err(self.error(CaptchaErrorKind::Transient, "No solution found")),
+                    };
+                }
+                Some("failed") => {
+                    return Err(self.error(CaptchaErrorKind::Transient, "Task failed"));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 提交已按平台格式构建的任务并等待结果
+    pub async fn solve(
+        &self,
+        task: &CaptchaTask,
+        payload: Map<String, Value>,
+    ) -> Result<CaptchaSolution, CaptchaError> {
+        let started = Instant::now();
+        let task_id = self.create_task(payload).await?;
+        let (solution, reported_cost) = self.wait_result(&task_id).await?;
+        let cost = reported_cost.or_else(|| {
+            self.prices
+                .get(task.kind.name())
+                .map(|per_thousand| per_thousand / 1000.0)
+        });
+
+        Ok(CaptchaSolution {
+            provider: self.provider,
+            task_id,
+            answer: parse_answer(self.provider, &task.kind, &solution)?,
+            user_agent: solution_str(&solution, "userAgent"),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            cost,
+        })
+    }
+}
+
+/// 平台不支持该验证码类型
+pub(super) fn unsupported(provider: CaptchaProvider, kind: &CaptchaKind) -> CaptchaError {
+    CaptchaError::new(
+        CaptchaErrorKind::Unsupported,
+        Some(provider),
+        format!("{} is not supported", kind.name()),
+    )
+}
+
+/// 按错误码归类（各平台错误码命名基本一致）
+fn classify_error(code: &str) -> CaptchaErrorKind {
+    const TRANSIENT: &[&str] = &[
+        "NO_SLOT",
+        "UNSOLVABLE",
+        "SERVICE_UNAVA",
+        "RATE_LIMIT",
+        "MAXIMUM_TIME",
+        "TOO_MUCH_REQUESTS",
+        "PROXY",
+        "TIMEOUT",
+    ];
+    const ACCOUNT: &[&str] = &["KEY", "BALANCE", "SETTLEMENT", "IP_BLOCKED", "ACCOUNT", "BANNED"];
+
+    let code = code.to_uppercase();
+    if TRANSIENT.iter().any(|p| code.contains(p)) {
+        CaptchaErrorKind::Transient
+    } else if ACCOUNT.iter().any(|p| code.contains(p)) {
+        CaptchaErrorKind::Account
+    } else {
+        CaptchaErrorKind::InvalidTask
+    }
+}
+
+/// 把代理URL拆成 proxyType/proxyAddress/proxyPort/proxyLogin/proxyPassword 字段
+pub(super) fn proxy_fields(provider: CaptchaProvider, proxy: &str) -> Result<Map<String, Value>, CaptchaError> {
+    let invalid = || CaptchaError::new(CaptchaErrorKind::InvalidTask, Some(provider), "Invalid proxy URL");
+    let url = reqwest::Url::parse(proxy).map_err(|_| invalid())?;
+
+    let mut fields = Map::new();
+    fields.insert("proxyType".into(), json!(url.scheme()));
+    fields.insert("proxyAddress".into(), json!(url.host_str().ok_or_else(invalid)?));
+    fields.insert(
+        "proxyPort".into(),
+        json!(url.port_or_known_default().ok_or_else(invalid)?),
+    );
+    if !url.username().is_empty() {
+        fields.insert("proxyLogin".into(), json!(url.username()));
+        fields.insert("proxyPassword".into(), json!(url.password().unwrap_or_default()));
+    }
+    Ok(fields)
+}
+
+/// solution 中的字符串字段（数字也按字符串取）
+fn solution_str(solution: &Map<String, Value>, key: &str) -> Option<String> {
+    match solution.get(key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// 把各平台的 solution 转为统一答案（字段命名各平台基本一致）
+fn parse_answer(
+    provider: CaptchaProvider,
+    kind: &CaptchaKind,
+    solution: &Map<String, Value>,
+) -> Result<CaptchaAnswer, CaptchaError> {
+    let field = |keys: &[&str]| keys.iter().find_map(|k| solution_str(solution, k));
+    let missing = |what: &str| {
+        CaptchaError::new(
+            CaptchaErrorKind::Transient,
+            Some(provider),
+            format!("Solution missing {}", what),
+        )
+    };
+
+    Ok(match kind {
+        CaptchaKind::Turnstile { .. } | CaptchaKind::RecaptchaV2 { .. } | CaptchaKind::RecaptchaV3 { .. } => {
+            CaptchaAnswer::Token(TokenSolution {
+                token: field(&["token", "gRecaptchaResponse"]).ok_or_else(|| missing("token"))?,
+            })
+        }
+        CaptchaKind::HCaptcha { .. } => CaptchaAnswer::HCaptcha(HCaptchaSolution {
+            token: field(&["gRecaptchaResponse", "token"]).ok_or_else(|| missing("token"))?,
+            resp_key: field(&["respKey"]),
+        }),
+        CaptchaKind::GeeTestV4 { captcha_id, .. } => {
+            let get = |key: &str| field(&[key]).ok_or_else(|| missing(key));
+            CaptchaAnswer::GeeTestV4(GeeTestV4Solution {
+                captcha_id: field(&["captcha_id"]).unwrap_or_else(|| captcha_id.clone()),
+                lot_number: get("lot_number")?,
+                pass_token: get("pass_token")?,
+                gen_time: get("gen_time")?,
+                captcha_output: get("captcha_output")?,
+            })
+        }
+        CaptchaKind::Image { .. } => CaptchaAnswer::Image(ImageSolution {
+            text: field(&["text"]).ok_or_else(|| missing("text"))?,
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::anti_captcha::AntiCaptcha;
+    use super::super::capsolver::CapSolver;
+    use super::super::two_captcha::TwoCaptcha;
+    use super::*;
+    use crate::common::recorder;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    fn solution(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    fn geetest_kind() -> CaptchaKind {
+        CaptchaKind::GeeTestV4 {
+            captcha_id: "244bcb8b9846215df5af4c624a750db4".to_string(),
+            api_server_subdomain: None,
+            init_parameters: None,
+        }
+    }
+
+    fn recaptcha_v3_kind() -> CaptchaKind {
+        CaptchaKind::RecaptchaV3 {
+            website_key: "6Lc-site-key".to_string(),
+            action: "login".to_string(),
+            min_score: Some(0.7),
+            enterprise: false,
+            api_domain: None,
+        }
+    }
+
+    fn hcaptcha_kind() -> CaptchaKind {
+        CaptchaKind::HCaptcha {
+            website_key: "10000000-ffff-ffff-ffff-000000000001".to_string(),
+            invisible: false,
+            enterprise_payload: None,
+        }
+    }
+
+    #[test]
+    fn classify_error_groups_provider_codes() {
+        for code in [
+            "ERROR_NO_SLOT_AVAILABLE",
+            "ERROR_CAPTCHA_UNSOLVABLE",
+            "ERR_PROXY_CONNECTION_FAILED",
+            "ERROR_TOO_MUCH_REQUESTS",
+            "ERROR_SERVICE_UNAVALIABLE",
+        ] {
+            assert_eq!(classify_error(code), CaptchaErrorKind::Transient, "{}", code);
+        }
+        for code in [
+            "ERROR_KEY_DOES_NOT_EXIST",
+            "ERROR_ZERO_BALANCE",
+            "ERROR_IP_BLOCKED",
+            "ERROR_ACCOUNT_SUSPENDED",
+        ] {
+            assert_eq!(classify_error(code), CaptchaErrorKind::Account, "{}", code);
+        }
+        assert_eq!(classify_error("ERROR_WRONG_PAGEURL"), CaptchaErrorKind::InvalidTask);
+        assert_eq!(classify_error("error_no_slot_available"), CaptchaErrorKind::Transient);
+    }
+
+    #[test]
+    fn parse_answer_reads_token_fields() {
+        let answer = parse_answer(
+            CaptchaProvider::TwoCaptcha,
+            &recaptcha_v3_kind(),
+            &solution(json!({"gRecaptchaResponse": "03AGdBq", "userAgent": "UA"})),
+        )
+        .unwrap();
+        assert!(matches!(answer, CaptchaAnswer::Token(t) if t.token == "03AGdBq"));
+
+        let answer = parse_answer(
+            CaptchaProvider::AntiCaptcha,
+            &hcaptcha_kind(),
+            &solution(json!({"gRecaptchaResponse": "P1_eyJ", "respKey": "E0_eyJ"})),
+        )
+        .unwrap();
+        match answer {
+            CaptchaAnswer::HCaptcha(h) => {
+                assert_eq!(h.token, "P1_eyJ");
+                assert_eq!(h.resp_key.as_deref(), Some("E0_eyJ"));
+            }
+            other => panic!("unexpected answer: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_answer_reads_geetest_fields() {
+        let answer = parse_answer(
+            CaptchaProvider::CapSolver,
+            &geetest_kind(),
+            &solution(json!({
+                "lot_number": "lot",
+                "pass_token": "pass",
+                // 部分平台把 gen_time 返回为数字
+                "gen_time": 1700000000,
+                "captcha_output": "output"
+            })),
+        )
+        .unwrap();
+        match answer {
+            CaptchaAnswer::GeeTestV4(g) => {
+                // 平台未返回 captcha_id 时使用任务中的
+                assert_eq!(g.captcha_id, "244bcb8b9846215df5af4c624a750db4");
+                assert_eq!(g.lot_number, "lot");
+                assert_eq!(g.pass_token, "pass");
+                assert_eq!(g.gen_time, "1700000000");
+                assert_eq!(g.captcha_output, "output");
+            }
+            other => panic!("unexpected answer: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_answer_reports_missing_fields() {
+        let err = parse_answer(
+            CaptchaProvider::CapSolver,
+            &geetest_kind(),
+            &solution(json!({"lot_number": "lot", "pass_token": "pass", "gen_time": "1"})),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, CaptchaErrorKind::Transient);
+        assert!(err.message.contains("captcha_output"), "{}", err.message);
+
+        let err = parse_answer(CaptchaProvider::TwoCaptcha, &hcaptcha_kind(), &Map::new()).unwrap_err();
+        assert!(err.message.contains("token"), "{}", err.message);
+    }
+
+    /// 本地模拟打码平台：createTask 返回任务ID，第一次查询处理中，第二次返回 solution
+    struct MockProvider {
+        base_url: String,
+        requests: Arc<Mutex<Vec<(String, Value)>>>,
+    }
+
+    async fn read_request(socket: &mut TcpStream) -> Option<(String, Value)> {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = socket.read(&mut buf).await.ok()?;
+            if n == 0 {
+                return None;
+            }
+            data.extend_from_slice(&buf[..n]);
+
+            let text = String::from_utf8_lossy(&data).to_string();
+            let (head, body) = match text.split_once("\r\n\r\n") {
+                Some(parts) => parts,
+                None => continue,
+            };
+            let length = head
+                .lines()
+                .find_map(|l| {
+                    let (name, value) = l.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if body.len() < length {
+                continue;
+            }
+
+            let path = head.split_whitespace().nth(1)?.to_string();
+            return Some((path, serde_json::from_str(body).unwrap_or(Value::Null)));
+        }
+    }
+
+    async fn mock_provider(task_id: Value, answer: Value) -> MockProvider {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+
+        tokio::spawn(async move {
+            let mut polls = 0;
+            loop {
+                let (mut socket, _) = match listener.accept().await {
+                    Ok(s) => s,
+                    Err(_) => return,
+                };
+                let (path, body) = match read_request(&mut socket).await {
+                    Some(r) => r,
+                    None => continue,
+                };
+                log.lock().unwrap().push((path.clone(), body));
+
+                let reply = match path.as_str() {
+                    "/createTask" => json!({"errorId": 0, "taskId": task_id}),
+                    "/getTaskResult" => {
+                        polls += 1;
+                        if polls == 1 {
+                            json!({"errorId": 0, "status": "processing"})
+                        } else {
+                            json!({"errorId": 0, "status": "ready", "solution": answer, "cost": "0.00299"})
+                        }
+                    }
+                    _ => json!({"errorId": 1, "errorCode": "ERROR_METHOD", "errorDescription": "unknown"}),
+                }
+                .to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    reply.len(),
+                    reply
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        MockProvider { base_url, requests }
+    }
+
+    fn settings() -> CaptchaSettings {
+        CaptchaSettings {
+            poll_interval_ms: 500,
+            ..Default::default()
+        }
+    }
+
+    fn provider_config(provider: CaptchaProvider, base_url: &str) -> CaptchaProviderConfig {
+        CaptchaProviderConfig {
+            provider,
+            api_key: "test-key".to_string(),
+            enabled: true,
+            base_url: Some(base_url.to_string()),
+            prices: HashMap::new(),
+        }
+    }
+
+    fn task(kind: CaptchaKind) -> CaptchaTask {
+        CaptchaTask {
+            website_url: "https://example.com/login".to_string(),
+            proxy: None,
+            kind,
+        }
+    }
+
+    #[tokio::test]
+    async fn solves_geetest_v4_against_mock_provider() {
+        let _guard = recorder::TEST_LOCK.lock().await;
+        let mock = mock_provider(
+            json!("cap-task-1"),
+            json!({
+                "captcha_id": "244bcb8b9846215df5af4c624a750db4",
+                "lot_number": "lot",
+                "pass_token": "pass",
+                "gen_time": "1700000000",
+                "captcha_output": "output"
+            }),
+        )
+        .await;
+        let solver = CapSolver::new(
+            &provider_config(CaptchaProvider::CapSolver, &mock.base_url),
+            &settings(),
+        )
+        .unwrap();
+
+        let result = solver.solve(&task(geetest_kind())).await.unwrap();
+        assert_eq!(result.task_id, "cap-task-1");
+        assert_eq!(result.cost, Some(0.00299));
+        assert!(matches!(result.answer, CaptchaAnswer::GeeTestV4(ref g) if g.pass_token == "pass"));
+
+        let requests = mock.requests.lock().unwrap().clone();
+        let paths: Vec<&str> = requests.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, ["/createTask", "/getTaskResult", "/getTaskResult"]);
+        assert_eq!(requests[0].1["clientKey"], "test-key");
+        assert_eq!(requests[0].1["task"]["type"], "GeeTestTaskProxyLess");
+        // CapSolver 的任务ID保持字符串
+        assert_eq!(requests[1].1["taskId"], "cap-task-1");
+    }
+
+    #[tokio::test]
+    async fn solves_recaptcha_v3_against_mock_provider() {
+        let _guard = recorder::TEST_LOCK.lock().await;
+        let mock = mock_provider(json!(72345678901u64), json!({"gRecaptchaResponse": "03AGdBq"})).await;
+        let solver = TwoCaptcha::new(
+            &provider_config(CaptchaProvider::TwoCaptcha, &mock.base_url),
+            &settings(),
+        )
+        .unwrap();
+
+        let result = solver.solve(&task(recaptcha_v3_kind())).await.unwrap();
+        assert_eq!(result.task_id, "72345678901");
+        assert!(matches!(result.answer, CaptchaAnswer::Token(ref t) if t.token == "03AGdBq"));
+
+        let requests = mock.requests.lock().unwrap().clone();
+        let created = &requests[0].1["task"];
+        assert_eq!(created["type"], "RecaptchaV3TaskProxyless");
+        assert_eq!(created["pageAction"], "login");
+        assert_eq!(created["minScore"].as_f64().map(|s| (s * 10.0).round()), Some(7.0));
+        // 其它平台的数字任务ID按数字查询
+        assert_eq!(requests[1].1["taskId"], json!(72345678901u64));
+    }
+
+    #[tokio::test]
+    async fn solves_hcaptcha_against_mock_provider() {
+        let _guard = recorder::TEST_LOCK.lock().await;
+        let mock = mock_provider(
+            json!(42),
+            json!({"gRecaptchaResponse": "P1_eyJ", "respKey": "E0_eyJ", "userAgent": "Mozilla/5.0"}),
+        )
+        .await;
+        let solver = AntiCaptcha::new(
+            &provider_config(CaptchaProvider::AntiCaptcha, &mock.base_url),
+            &settings(),
+        )
+        .unwrap();
+
+        let result = solver.solve(&task(hcaptcha_kind())).await.unwrap();
+        assert_eq!(result.user_agent.as_deref(), Some("Mozilla/5.0"));
+        match result.answer {
+            CaptchaAnswer::HCaptcha(h) => {
+                assert_eq!(h.token, "P1_eyJ");
+                assert_eq!(h.resp_key.as_deref(), Some("E0_eyJ"));
+            }
+            other => panic!("unexpected answer: {:?}", other),
+        }
+
+        let requests = mock.requests.lock().unwrap().clone();
+        assert_eq!(requests[0].1["task"]["websiteKey"], "10000000-ffff-ffff-ffff-000000000001");
+    }
+}
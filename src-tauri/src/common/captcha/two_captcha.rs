@@ -0,0 +1,163 @@
+use super::task_api::{proxy_fields, TaskApi};
+use super::types::*;
+use serde_json::{json, Map, Value};
+
+/// 2Captcha（API v2，协议与 Anti-Captcha 兼容）
+pub struct TwoCaptcha {
+    api: TaskApi,
+}
+
+impl TwoCaptcha {
+    pub fn new(config: &CaptchaProviderConfig, settings: &CaptchaSettings) -> Result<Self, CaptchaError> {
+        Ok(Self {
+            api: TaskApi::new(config, settings)?,
+        })
+    }
+
+    fn build_task(&self, task: &CaptchaTask) -> Result<Map<String, Value>, CaptchaError> {
+        let mut payload = Map::new();
+        if !task.website_url.is_empty() {
+            payload.insert("websiteURL".into(), json!(task.website_url));
+        }
+
+        let proxied = task.proxy.is_some();
+        let task_type = |base: &str| {
+            if proxied {
+                base.to_string()
+            } else {
+                format!("{}Proxyless", base)
+            }
+        };
+
+        let uses_proxy = match &task.kind {
+            CaptchaKind::Turnstile {
+                website_key,
+                action,
+                cdata,
+            } => {
+                payload.insert("type".into(), json!(task_type("TurnstileTask")));
+                payload.insert("websiteKey".into(), json!(website_key));
+                if let Some(action) = action {
+                    payload.insert("action".into(), json!(action));
+                }
+                if let Some(cdata) = cdata {
+                    payload.insert("data".into(), json!(cdata));
+                }
+                true
+            }
+            CaptchaKind::RecaptchaV2 {
+                website_key,
+                invisible,
+                data_s,
+                enterprise,
+                enterprise_payload,
+                api_domain,
+            } => {
+                let base = if *enterprise {
+                    "RecaptchaV2EnterpriseTask"
+                } else {
+                    "RecaptchaV2Task"
+                };
+                payload.insert("type".into(), json!(task_type(base)));
+                payload.insert("websiteKey".into(), json!(website_key));
+                payload.insert("isInvisible".into(), json!(invisible));
+                if let Some(s) = data_s {
+                    payload.insert("recaptchaDataSValue".into(), json!(s));
+                }
+                if let (true, Some(p)) = (*enterprise, enterprise_payload) {
+                    payload.insert("enterprisePayload".into(), p.clone());
+                }
+                if let Some(domain) = api_domain {
+                    payload.insert("apiDomain".into(), json!(domain));
+                }
+                true
+            }
+            CaptchaKind::RecaptchaV3 {
+                website_key,
+                action,
+                min_score,
+                enterprise,
+                api_domain,
+            } => {
+                // v3 只有无代理版本
+                payload.insert("type".into(), json!("RecaptchaV3TaskProxyless"));
+                payload.insert("websiteKey".into(), json!(website_key));
+                payload.insert("pageAction".into(), json!(action));
+                payload.insert("minScore".into(), json!(min_score.unwrap_or(0.3)));
+                payload.insert("isEnterprise".into(), json!(enterprise));
+                if let Some(domain) = api_domain {
+                    payload.insert("apiDomain".into(), json!(domain));
+                }
+                false
+            }
+            CaptchaKind::HCaptcha {
+                website_key,
+                invisible,
+                enterprise_payload,
+            } => {
+                payload.insert("type".into(), json!(task_type("HCaptchaTask")));
+                payload.insert("websiteKey".into(), json!(website_key));
+                payload.insert("isInvisible".into(), json!(invisible));
+                if let Some(p) = enterprise_payload {
+                    payload.insert("enterprisePayload".into(), p.clone());
+                }
+                true
+            }
+            CaptchaKind::GeeTestV4 {
+                captcha_id,
+                api_server_subdomain,
+                init_parameters,
+            } => {
+                payload.insert("type".into(), json!(task_type("GeeTestTask")));
+                payload.insert("version".into(), json!(4));
+
+                let mut init = match init_parameters {
+                    Some(Value::Object(p)) => p.clone(),
+                    _ => Map::new(),
+                };
+                init.insert("captcha_id".into(), json!(captcha_id));
+                payload.insert("initParameters".into(), Value::Object(init));
+                if let Some(subdomain) = api_server_subdomain {
+                    payload.insert("geetestApiServerSubdomain".into(), json!(subdomain));
+                }
+                true
+            }
+            CaptchaKind::Image {
+                body,
+                case_sensitive,
+                numeric,
+                comment,
+            } => {
+                payload.insert("type".into(), json!("ImageToTextTask"));
+                payload.insert("body".into(), json!(body));
+                payload.insert("case".into(), json!(case_sensitive));
+                if *numeric {
+                    payload.insert("numeric".into(), json!(1));
+                }
+                if let Some(comment) = comment {
+                    payload.insert("comment".into(), json!(comment));
+                }
+                false
+            }
+        };
+
+        if let (Some(proxy), true) = (&task.proxy, uses_proxy) {
+            payload.extend(proxy_fields(self.provider(), proxy)?);
+        }
+        Ok(payload)
+    }
+}
+
+impl CaptchaSolver for TwoCaptcha {
+    fn provider(&self) -> CaptchaProvider {
+        CaptchaProvider::TwoCaptcha
+    }
+
+    fn solve<'a>(&'a self, task: &'a CaptchaTask) -> SolveFuture<'a, CaptchaSolution> {
+        Box::pin(async move { self.api.solve(task, self.build_task(task)?).await })
+    }
+
+    fn balance(&self) -> SolveFuture<'_, f64> {
+        Box::pin(self.api.balance())
+    }
+}
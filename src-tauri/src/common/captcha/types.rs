@@ -0,0 +1,348 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+/// 打码平台
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CaptchaProvider {
+    #[serde(rename = "capsolver")]
+    CapSolver,
+    #[serde(rename = "2captcha")]
+    TwoCaptcha,
+    #[serde(rename = "anticaptcha")]
+    AntiCaptcha,
+    #[serde(rename = "capmonster")]
+    CapMonster,
+}
+
+impl CaptchaProvider {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CapSolver => "CapSolver",
+            Self::TwoCaptcha => "2Captcha",
+            Self::AntiCaptcha => "Anti-Captcha",
+            Self::CapMonster => "CapMonster",
+        }
+    }
+
+    /// 官方接口地址
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            Self::CapSolver => "https://api.capsolver.com",
+            Self::TwoCaptcha => "https://api.2captcha.com",
+            Self::AntiCaptcha => "https://api.anti-captcha.com",
+            Self::CapMonster => "https://api.capmonster.cloud",
+        }
+    }
+}
+
+/// 验证码类型及其参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CaptchaKind {
+    /// Cloudflare Turnstile
+    Turnstile {
+        website_key: String,
+        #[serde(default)]
+        action: Option<String>,
+        #[serde(default)]
+        cdata: Option<String>,
+    },
+    /// reCAPTCHA v2（含 invisible / Enterprise）
+    RecaptchaV2 {
+        website_key: String,
+        #[serde(default)]
+        invisible: bool,
+        /// 页面上的 data-s 参数（Google 服务常见）
+        #[serde(default)]
+        data_s: Option<String>,
+        #[serde(default)]
+        enterprise: bool,
+        /// Enterprise 版 grecaptcha.enterprise.render 的额外参数（如 s）
+        #[serde(default)]
+        enterprise_payload: Option<serde_json::Value>,
+        /// 加载脚本的域名（google.com / recaptcha.net）
+        #[serde(default)]
+        api_domain: Option<String>,
+    },
+    /// reCAPTCHA v3（含 Enterprise）
+    RecaptchaV3 {
+        website_key: String,
+        action: String,
+        /// 需要的最低分数（0.3 / 0.7 / 0.9），平台不支持时忽略
+        #[serde(default)]
+        min_score: Option<f32>,
+        #[serde(default)]
+        enterprise: bool,
+        #[serde(default)]
+        api_domain: Option<String>,
+    },
+    /// hCaptcha（含 Enterprise）
+    HCaptcha {
+        website_key: String,
+        #[serde(default)]
+        invisible: bool,
+        /// Enterprise 版的 rqdata 等参数
+        #[serde(default)]
+        enterprise_payload: Option<serde_json::Value>,
+    },
+    /// GeeTest v4（Galxe 等）
+    GeeTestV4 {
+        captcha_id: String,
+        /// 自定义 API 子域名（如 gcaptcha4.geetest.com 以外的部署）
+        #[serde(default)]
+        api_server_subdomain: Option<String>,
+        /// initGeetest4 的额外初始化参数
+        #[serde(default)]
+        init_parameters: Option<serde_json::Value>,
+    },
+    /// 图片文字识别
+    Image {
+        /// 图片 base64（不含 data: 前缀）
+        body: String,
+        #[serde(default)]
+        case_sensitive: bool,
+        /// 只含数字
+        #[serde(default)]
+        numeric: bool,
+        /// 给打码工人的提示
+        #[serde(default)]
+        comment: Option<String>,
+    },
+}
+
+impl CaptchaKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Turnstile { .. } => "turnstile",
+            Self::RecaptchaV2 { .. } => "recaptcha_v2",
+            Self::RecaptchaV3 { .. } => "recaptcha_v3",
+            Self::HCaptcha { .. } => "hcaptcha",
+            Self::GeeTestV4 { .. } => "geetest_v4",
+            Self::Image { .. } => "image",
+        }
+    }
+}
+
+/// 一次打码任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptchaTask {
+    /// 验证码所在页面（图片识别可为空）
+    #[serde(default)]
+    pub website_url: String,
+    /// 让打码平台通过该代理访问目标站点（与钱包出口一致），None 时由平台自行访问
+    #[serde(default)]
+    pub proxy: Option<String>,
+    pub kind: CaptchaKind,
+}
+
+/// Turnstile / reCAPTCHA 的令牌
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenSolution {
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HCaptchaSolution {
+    pub token: String,
+    /// Enterprise 站点需要一并提交的 respKey
+    pub resp_key: Option<String>,
+}
+
+/// GeeTest v4 校验参数，提交时原样带上
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeeTestV4Solution {
+    pub captcha_id: String,
+    pub lot_number: String,
+    pub pass_token: String,
+    pub gen_time: String,
+    pub captcha_output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageSolution {
+    pub text: String,
+}
+
+/// 按验证码类型区分的答案
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CaptchaAnswer {
+    Token(TokenSolution),
+    HCaptcha(HCaptchaSolution),
+    GeeTestV4(GeeTestV4Solution),
+    Image(ImageSolution),
+}
+
+/// 打码结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptchaSolution {
+    pub provider: CaptchaProvider,
+    pub task_id: String,
+    pub answer: CaptchaAnswer,
+    /// 平台解题时使用的 UA（部分站点要求提交时一致）
+    pub user_agent: Option<String>,
+    pub elapsed_ms: u64,
+    /// 本次花费（美元）：平台返回的费用，未返回时按配置的单价估算
+    pub cost: Option<f64>,
+}
+
+impl CaptchaSolution {
+    /// 令牌类答案（Turnstile / reCAPTCHA / hCaptcha）
+    pub fn token(&self) -> Option<&str> {
+        match &self.answer {
+            CaptchaAnswer::Token(t) => Some(&t.token),
+            CaptchaAnswer::HCaptcha(h) => Some(&h.token),
+            _ => None,
+        }
+    }
+
+    pub fn geetest(&self) -> Option<&GeeTestV4Solution> {
+        match &self.answer {
+            CaptchaAnswer::GeeTestV4(g) => Some(g),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        match &self.answer {
+            CaptchaAnswer::Image(i) => Some(&i.text),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptchaErrorKind {
+    /// 平台暂时无法处理（无空闲工人、本次无法识别、超时），可重试或换平台
+    Transient,
+    /// 密钥无效、余额不足、IP 被封等账户问题，换平台
+    Account,
+    /// 任务参数错误，换平台也无济于事
+    InvalidTask,
+    /// 该平台不支持此验证码类型，换平台
+    Unsupported,
+    /// 网络请求失败
+    Network,
+    /// 没有可用的打码平台配置
+    NotConfigured,
+    /// 超出每日/单次运行的打码预算
+    BudgetExceeded,
+}
+
+/// 各平台统一的打码错误
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptchaError {
+    pub kind: CaptchaErrorKind,
+    pub provider: Option<CaptchaProvider>,
+    /// 平台返回的错误码（如 ERROR_NO_SLOT_AVAILABLE）
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl CaptchaError {
+    pub fn new(kind: CaptchaErrorKind, provider: Option<CaptchaProvider>, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            provider,
+            code: None,
+            message: message.into(),
+        }
+    }
+
+    /// 同一平台重试可能成功
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.kind, CaptchaErrorKind::Transient | CaptchaErrorKind::Network)
+    }
+
+    /// 换一个平台可能成功
+    pub fn should_fall_over(&self) -> bool {
+        !matches!(
+            self.kind,
+            CaptchaErrorKind::InvalidTask | CaptchaErrorKind::BudgetExceeded
+        )
+    }
+}
+
+impl std::fmt::Display for CaptchaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(provider) = self.provider {
+            write!(f, "[{}] ", provider.name())?;
+        }
+        match &self.code {
+            Some(code) => write!(f, "{}: {}", code, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for CaptchaError {}
+
+impl From<CaptchaError> for String {
+    fn from(e: CaptchaError) -> Self {
+        e.to_string()
+    }
+}
+
+pub type SolveFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, CaptchaError>> + Send + 'a>>;
+
+/// 打码平台实现
+pub trait CaptchaSolver: Send + Sync {
+    fn provider(&self) -> CaptchaProvider;
+
+    /// 提交任务并等待结果
+    fn solve<'a>(&'a self, task: &'a CaptchaTask) -> SolveFuture<'a, CaptchaSolution>;
+
+    /// 账户余额（美元）
+    fn balance(&self) -> SolveFuture<'_, f64>;
+}
+
+/// 单个打码平台配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptchaProviderConfig {
+    pub provider: CaptchaProvider,
+    pub api_key: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 自定义接口地址（兼容协议的自建/代理服务），为空使用官方地址
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// 每千次单价（美元），键为验证码类型名；平台不返回费用时用于估算
+    #[serde(default)]
+    pub prices: HashMap<String, f64>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 打码设置：按 providers 顺序依次尝试
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptchaSettings {
+    pub providers: Vec<CaptchaProviderConfig>,
+    /// 每个平台遇到临时错误时的最多尝试次数
+    pub attempts_per_provider: u32,
+    pub poll_interval_ms: u64,
+    /// 单次任务等待结果的超时
+    pub timeout_secs: u64,
+    /// 每日（UTC）打码花费上限（美元），None 不限
+    pub daily_budget: Option<f64>,
+    /// 单次脚本运行的打码花费上限（美元），None 不限
+    pub run_budget: Option<f64>,
+}
+
+impl Default for CaptchaSettings {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            attempts_per_provider: 2,
+            poll_interval_ms: 2000,
+            timeout_secs: 120,
+            daily_budget: None,
+            run_budget: None,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use super::types::*;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref LEDGER: Mutex<SpendLedger> = Mutex::new(SpendLedger::default());
+}
+
+/// 打码请求归属，用于花费统计和预算
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CaptchaUsageTags {
+    pub script: Option<String>,
+    pub wallet: Option<String>,
+    pub run_id: Option<String>,
+}
+
+/// 单次打码记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptchaSolveRecord {
+    pub solved_at: String,
+    pub provider: CaptchaProvider,
+    pub kind: String,
+    pub duration_ms: u64,
+    /// 美元，失败且平台未收费时为 0
+    pub cost: f64,
+    pub success: bool,
+    pub error: Option<String>,
+    pub script: Option<String>,
+    pub wallet: Option<String>,
+    pub run_id: Option<String>,
+}
+
+#[derive(Default)]
+struct SpendLedger {
+    /// 当前统计日（UTC YYYY-MM-DD）
+    day: String,
+    today: f64,
+    per_run: HashMap<String, f64>,
+    /// 尚未落盘的记录
+    pending: Vec<CaptchaSolveRecord>,
+}
+
+impl SpendLedger {
+    fn roll_day(&mut self) {
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        if self.day != today {
+            self.day = today;
+            self.today = 0.0;
+        }
+    }
+}
+
+/// 启动时用已落盘的当日花费初始化
+pub fn seed_today(spent: f64) {
+    let mut ledger = LEDGER.lock().unwrap();
+    ledger.roll_day();
+    ledger.today = ledger.today.max(spent);
+}
+
+/// 记录一次打码并累计花费
+pub fn record(record: CaptchaSolveRecord) {
+    let mut ledger = LEDGER.lock().unwrap();
+    ledger.roll_day();
+
+    ledger.today += record.cost;
+    if let Some(run_id) = &record.run_id {
+        *ledger.per_run.entry(run_id.clone()).or_default() += record.cost;
+    }
+    ledger.pending.push(record);
+}
+
+/// 取出所有未落盘的记录
+pub fn take_pending() -> Vec<CaptchaSolveRecord> {
+    std::mem::take(&mut LEDGER.lock().unwrap().pending)
+}
+
+/// 落盘失败时放回未落盘的记录（花费已累计，不重复计入）
+pub fn restore_pending(mut records: Vec<CaptchaSolveRecord>) {
+    let mut ledger = LEDGER.lock().unwrap();
+    records.append(&mut ledger.pending);
+    ledger.pending = records;
+}
+
+/// 当日已花费
+pub fn spent_today() -> f64 {
+    let mut ledger = LEDGER.lock().unwrap();
+    ledger.roll_day();
+    ledger.today
+}
+
+/// 某次运行已花费
+pub fn spent_in_run(run_id: &str) -> f64 {
+    LEDGER
+        .lock()
+        .unwrap()
+        .per_run
+        .get(run_id)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// 检查预算，超出时返回 `BudgetExceeded`
+pub fn check_budget(
+    tags: &CaptchaUsageTags,
+    daily_budget: Option<f64>,
+    run_budget: Option<f64>,
+) -> Result<(), CaptchaError> {
+    if let Some(limit) = daily_budget {
+        let spent = spent_today();
+        if spent >= limit {
+            return Err(CaptchaError::new(
+                CaptchaErrorKind::BudgetExceeded,
+                None,
+                format!("Daily captcha budget exhausted: ${:.4} / ${:.2}", spent, limit),
+            ));
+        }
+    }
+
+    if let (Some(limit), Some(run_id)) = (run_budget, &tags.run_id) {
+        let spent = spent_in_run(run_id);
+        if spent >= limit {
+            return Err(CaptchaError::new(
+                CaptchaErrorKind::BudgetExceeded,
+                None,
+                format!("Run captcha budget exhausted: ${:.4} / ${:.2}", spent, limit),
+            ));
+        }
+    }
+
+    Ok(())
+}
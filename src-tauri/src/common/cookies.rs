@@ -0,0 +1,378 @@
+use lazy_static::lazy_static;
+use reqwest::header::HeaderValue;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+lazy_static! {
+    static ref JARS: Mutex<HashMap<String, Arc<CookieJar>>> = Mutex::new(HashMap::new());
+}
+
+/// 持久化的单条 cookie
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoredCookie {
+    pub name: String,
+    pub value: String,
+    /// 小写、不带前导点
+    pub domain: String,
+    pub path: String,
+    /// true 表示只发给设置它的主机，不含子域名
+    pub host_only: bool,
+    pub secure: bool,
+    pub http_only: bool,
+    /// 过期时间（unix 秒），None 为会话 cookie
+    pub expires: Option<i64>,
+}
+
+impl StoredCookie {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires.is_some_and(|e| e <= now)
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        let host = match url.host_str() {
+            Some(h) => h.to_lowercase(),
+            None => return false,
+        };
+
+        let domain_ok = if self.host_only {
+            host == self.domain
+        } else {
+            host == self.domain || host.ends_with(&format!(".{}", self.domain))
+        };
+        if !domain_ok {
+            return false;
+        }
+
+        if self.secure && url.scheme() != "https" {
+            return false;
+        }
+
+        let path = url.path();
+        path == self.path
+            || (path.starts_with(&self.path)
+                && (self.path.ends_with('/') || path[self.path.len()..].starts_with('/')))
+    }
+
+    /// 解析 Set-Cookie 头
+    fn from_set_cookie(header: &str, url: &Url, now: i64) -> Option<Self> {
+        let cookie = cookie::Cookie::parse(header.to_string()).ok()?;
+        let host = url.host_str()?.to_lowercase();
+
+        let (domain, host_only) = match cookie.domain() {
+            Some(d) => {
+                let d = d.trim_start_matches('.').to_lowercase();
+                // 只接受本域或父域
+                if host != d && !host.ends_with(&format!(".{}", d)) {
+                    return None;
+                }
+                (d, false)
+            }
+            None => (host, true),
+        };
+
+        let path = match cookie.path() {
+            Some(p) if p.starts_with('/') => p.to_string(),
+            _ => default_path(url),
+        };
+
+        // Max-Age 优先于 Expires
+        let expires = match cookie.max_age() {
+            Some(age) => Some(now + age.whole_seconds()),
+            None => cookie.expires_datetime().map(|t| t.unix_timestamp()),
+        };
+
+        Some(Self {
+            name: cookie.name().to_string(),
+            value: cookie.value().to_string(),
+            domain,
+            path,
+            host_only,
+            secure: cookie.secure().unwrap_or(false),
+            http_only: cookie.http_only().unwrap_or(false),
+            expires,
+        })
+    }
+}
+
+fn default_path(url: &Url) -> String {
+    let path = url.path();
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(i) => path[..i].to_string(),
+    }
+}
+
+/// 单个钱包的 cookie 罐，同一钱包的所有客户端共享
+pub struct CookieJar {
+    cookies: RwLock<Vec<StoredCookie>>,
+    dirty: AtomicBool,
+}
+
+impl CookieJar {
+    pub fn new(cookies: Vec<StoredCookie>) -> Self {
+        let now = chrono::Utc::now().timestamp();
+        Self {
+            cookies: RwLock::new(cookies.into_iter().filter(|c| !c.is_expired(now)).collect()),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// 写入或替换 cookie（同名同域同路径视为同一条），已过期的视为删除
+    pub fn upsert(&self, cookie: StoredCookie) {
+        let now = chrono::Utc::now().timestamp();
+        let mut cookies = self.cookies.write().unwrap();
+        cookies.retain(|c| {
+            !(c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path)
+        });
+        if !cookie.is_expired(now) {
+            cookies.push(cookie);
+        }
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// 未过期的 cookie
+    pub fn snapshot(&self) -> Vec<StoredCookie> {
+        let now = chrono::Utc::now().timestamp();
+        self.cookies
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|c| !c.is_expired(now))
+            .cloned()
+            .collect()
+    }
+
+    /// 删除 cookie，domain 为 None 时清空，返回删除条数
+    pub fn remove_domain(&self, domain: Option<&str>) -> usize {
+        let mut cookies = self.cookies.write().unwrap();
+        let before = cookies.len();
+        match domain {
+            Some(d) => {
+                let d = d.trim_start_matches('.').to_lowercase();
+                cookies.retain(|c| c.domain != d && !c.domain.ends_with(&format!(".{}", d)));
+            }
+            None => cookies.clear(),
+        }
+        let removed = before - cookies.len();
+        if removed > 0 {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        removed
+    }
+
+    /// 取出是否有未落盘的修改
+    fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::Relaxed)
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let now = chrono::Utc::now().timestamp();
+        for header in cookie_headers {
+            if let Some(cookie) = header
+                .to_str()
+                .ok()
+                .and_then(|h| StoredCookie::from_set_cookie(h, url, now))
+            {
+                self.upsert(cookie);
+            }
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let now = chrono::Utc::now().timestamp();
+        let mut matched: Vec<StoredCookie> = self
+            .cookies
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|c| !c.is_expired(now) && c.matches(url))
+            .cloned()
+            .collect();
+        if matched.is_empty() {
+            return None;
+        }
+
+        // 路径更长的在前
+        matched.sort_by_key(|c| std::cmp::Reverse(c.path.len()));
+        let header = matched
+            .iter()
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        HeaderValue::from_str(&header).ok()
+    }
+}
+
+/// 获取已加载的钱包 cookie 罐
+pub fn jar_for(wallet: &str) -> Option<Arc<CookieJar>> {
+    JARS.lock().unwrap().get(&wallet.to_lowercase()).cloned()
+}
+
+/// 注册钱包 cookie 罐（已存在时返回已有的）
+pub fn register_jar(wallet: &str, cookies: Vec<StoredCookie>) -> Arc<CookieJar> {
+    JARS.lock()
+        .unwrap()
+        .entry(wallet.to_lowercase())
+        .or_insert_with(|| Arc::new(CookieJar::new(cookies)))
+        .clone()
+}
+
+/// 取出所有有修改的 cookie 罐（钱包地址, 当前内容）
+pub fn take_dirty_jars() -> Vec<(String, Vec<StoredCookie>)> {
+    JARS.lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, jar)| jar.take_dirty())
+        .map(|(wallet, jar)| (wallet.clone(), jar.snapshot()))
+        .collect()
+}
+
+/// 落盘失败时重新标记为有修改，等待下次写入
+pub fn mark_dirty(wallets: &[String]) {
+    let jars = JARS.lock().unwrap();
+    for wallet in wallets {
+        if let Some(jar) = jars.get(wallet) {
+            jar.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 解析 Netscape cookies.txt
+pub fn parse_netscape(content: &str) -> Vec<StoredCookie> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_end_matches('\r');
+            let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+                Some(rest) => (rest, true),
+                None => (line, false),
+            };
+            if line.trim().is_empty() || line.starts_with('#') {
+                return None;
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 7 {
+                return None;
+            }
+
+            let expires = fields[4].parse::<i64>().ok().filter(|e| *e > 0);
+            Some(StoredCookie {
+                name: fields[5].to_string(),
+                value: fields[6].to_string(),
+                domain: fields[0].trim_start_matches('.').to_lowercase(),
+                path: fields[2].to_string(),
+                host_only: !fields[1].eq_ignore_ascii_case("TRUE"),
+                secure: fields[3].eq_ignore_ascii_case("TRUE"),
+                http_only,
+                expires,
+            })
+        })
+        .collect()
+}
+
+/// 导出为 Netscape cookies.txt
+pub fn to_netscape(cookies: &[StoredCookie]) -> String {
+    let mut out = String::from("# Netscape HTTP Cookie File\n");
+    for c in cookies {
+        let domain = if c.host_only {
+            c.domain.clone()
+        } else {
+            format!(".{}", c.domain)
+        };
+        out.push_str(&format!(
+            "{}{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            if c.http_only { "#HttpOnly_" } else { "" },
+            domain,
+            if c.host_only { "FALSE" } else { "TRUE" },
+            c.path,
+            if c.secure { "TRUE" } else { "FALSE" },
+            c.expires.unwrap_or(0),
+            c.name,
+            c.value
+        ));
+    }
+    out
+}
+
+/// 浏览器扩展（Cookie-Editor / EditThisCookie）导出的 JSON 格式
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowserJsonCookie {
+    name: String,
+    value: String,
+    domain: String,
+    #[serde(default = "root_path")]
+    path: String,
+    #[serde(default)]
+    host_only: Option<bool>,
+    #[serde(default)]
+    secure: bool,
+    #[serde(default)]
+    http_only: bool,
+    #[serde(default)]
+    session: Option<bool>,
+    #[serde(default)]
+    expiration_date: Option<f64>,
+}
+
+fn root_path() -> String {
+    "/".to_string()
+}
+
+/// 解析浏览器导出的 JSON cookie 数组
+pub fn parse_json(content: &str) -> Result<Vec<StoredCookie>, String> {
+    let items: Vec<BrowserJsonCookie> =
+        serde_json::from_str(content).map_err(|e| format!("无效的 cookie JSON: {}", e))?;
+
+    Ok(items
+        .into_iter()
+        .map(|c| {
+            let host_only = c.host_only.unwrap_or(!c.domain.starts_with('.'));
+            let expires = if c.session == Some(true) {
+                None
+            } else {
+                c.expiration_date.map(|e| e as i64)
+            };
+            StoredCookie {
+                name: c.name,
+                value: c.value,
+                domain: c.domain.trim_start_matches('.').to_lowercase(),
+                path: c.path,
+                host_only,
+                secure: c.secure,
+                http_only: c.http_only,
+                expires,
+            }
+        })
+        .collect())
+}
+
+/// 导出为浏览器扩展可导入的 JSON
+pub fn to_json(cookies: &[StoredCookie]) -> Result<String, String> {
+    let items: Vec<BrowserJsonCookie> = cookies
+        .iter()
+        .map(|c| BrowserJsonCookie {
+            name: c.name.clone(),
+            value: c.value.clone(),
+            domain: if c.host_only {
+                c.domain.clone()
+            } else {
+                format!(".{}", c.domain)
+            },
+            path: c.path.clone(),
+            host_only: Some(c.host_only),
+            secure: c.secure,
+            http_only: c.http_only,
+            session: Some(c.expires.is_none()),
+            expiration_date: c.expires.map(|e| e as f64),
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&items).map_err(|e| e.to_string())
+}
@@ -0,0 +1,266 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE, USER_AGENT};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrowserKind {
+    Chrome,
+    Edge,
+    Brave,
+    Firefox,
+    Safari,
+}
+
+impl BrowserKind {
+    /// 是否为 Chromium 内核（会发送 sec-ch-ua 客户端提示）
+    pub fn is_chromium(&self) -> bool {
+        matches!(self, Self::Chrome | Self::Edge | Self::Brave)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OsKind {
+    Windows,
+    MacOs,
+    Linux,
+}
+
+impl OsKind {
+    /// sec-ch-ua-platform 中的平台名
+    pub fn platform_name(&self) -> &'static str {
+        match self {
+            Self::Windows => "Windows",
+            Self::MacOs => "macOS",
+            Self::Linux => "Linux",
+        }
+    }
+}
+
+/// 浏览器指纹：同一钱包的所有请求使用同一份，生成后持久化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserProfile {
+    pub browser: BrowserKind,
+    pub browser_version: u32,
+    pub os: OsKind,
+    pub user_agent: String,
+    pub accept_language: String,
+    pub timezone: String,
+    pub screen_width: u32,
+    pub screen_height: u32,
+    pub device_pixel_ratio: f32,
+    pub created_at: String,
+}
+
+/// (浏览器, 系统, 权重)，按桌面端市场份额粗略取值
+const BROWSER_WEIGHTS: &[(BrowserKind, OsKind, u32)] = &[
+    (BrowserKind::Chrome, OsKind::Windows, 45),
+    (BrowserKind::Chrome, OsKind::MacOs, 14),
+    (BrowserKind::Chrome, OsKind::Linux, 4),
+    (BrowserKind::Edge, OsKind::Windows, 12),
+    (BrowserKind::Brave, OsKind::Windows, 4),
+    (BrowserKind::Brave, OsKind::MacOs, 2),
+    (BrowserKind::Firefox, OsKind::Windows, 7),
+    (BrowserKind::Firefox, OsKind::MacOs, 2),
+    (BrowserKind::Firefox, OsKind::Linux, 2),
+    (BrowserKind::Safari, OsKind::MacOs, 8),
+];
+
+/// (语言, 时区候选)
+const LOCALES: &[(&str, &[&str], u32)] = &[
+    (
+        "en-US",
+        &["America/New_York", "America/Chicago", "America/Denver", "America/Los_Angeles"],
+        55,
+    ),
+    ("en-GB", &["Europe/London"], 10),
+    ("de-DE", &["Europe/Berlin"], 7),
+    ("fr-FR", &["Europe/Paris"], 6),
+    ("es-ES", &["Europe/Madrid"], 4),
+    ("nl-NL", &["Europe/Amsterdam"], 3),
+    ("pt-BR", &["America/Sao_Paulo"], 5),
+    ("ja-JP", &["Asia/Tokyo"], 4),
+    ("ko-KR", &["Asia/Seoul"], 3),
+    ("en-SG", &["Asia/Singapore"], 3),
+];
+
+const WINDOWS_SCREENS: &[(u32, u32, f32, u32)] = &[
+    (1920, 1080, 1.0, 40),
+    (1536, 864, 1.25, 14),
+    (1366, 768, 1.0, 12),
+    (2560, 1440, 1.0, 12),
+    (1440, 900, 1.0, 6),
+    (1600, 900, 1.0, 6),
+    (1280, 720, 1.5, 5),
+    (1920, 1200, 1.0, 5),
+];
+
+const MAC_SCREENS: &[(u32, u32, f32, u32)] = &[
+    (1440, 900, 2.0, 25),
+    (1512, 982, 2.0, 25),
+    (1728, 1117, 2.0, 15),
+    (1680, 1050, 2.0, 10),
+    (1920, 1080, 1.0, 10),
+    (2560, 1440, 1.0, 15),
+];
+
+fn pick_weighted<T>(items: &[T], weight: impl Fn(&T) -> u32) -> &T {
+    items
+        .choose_weighted(&mut rand::thread_rng(), weight)
+        .expect("weight table must not be empty")
+}
+
+impl BrowserProfile {
+    /// 按真实分布随机生成一份指纹
+    pub fn generate() -> Self {
+        let mut rng = rand::thread_rng();
+
+        let &(browser, os, _) = pick_weighted(BROWSER_WEIGHTS, |w| w.2);
+        let browser_version = match browser {
+            BrowserKind::Chrome | BrowserKind::Edge | BrowserKind::Brave => rng.gen_range(134..=140),
+            BrowserKind::Firefox => rng.gen_range(136..=143),
+            BrowserKind::Safari => *[17u32, 18].choose(&mut rng).unwrap(),
+        };
+
+        let &(locale, timezones, _) = pick_weighted(LOCALES, |l| l.2);
+        let timezone = timezones.choose(&mut rng).unwrap().to_string();
+
+        let screens = if os == OsKind::MacOs { MAC_SCREENS } else { WINDOWS_SCREENS };
+        let &(screen_width, screen_height, device_pixel_ratio, _) = pick_weighted(screens, |s| s.3);
+
+        let safari_minor = rng.gen_range(0..=6);
+
+        Self {
+            browser,
+            browser_version,
+            os,
+            user_agent: build_user_agent(browser, browser_version, os, safari_minor),
+            accept_language: build_accept_language(browser, locale),
+            timezone,
+            screen_width,
+            screen_height,
+            device_pixel_ratio,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// sec-ch-ua 品牌列表（非 Chromium 浏览器返回 None）
+    ///
+    /// GREASE 品牌与排序按 Chromium 的算法由主版本号决定
+    pub fn sec_ch_ua(&self) -> Option<String> {
+        let brand = match self.browser {
+            BrowserKind::Chrome => "Google Chrome",
+            BrowserKind::Edge => "Microsoft Edge",
+            BrowserKind::Brave => "Brave",
+            _ => return None,
+        };
+
+        const GREASE_CHARS: [&str; 11] = [" ", "(", ":", "-", ".", "/", ")", ";", "=", "?", "_"];
+        const GREASE_VERSIONS: [&str; 3] = ["8", "99", "24"];
+        const ORDERS: [[usize; 3]; 6] = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+
+        let v = self.browser_version as usize;
+        let grease = format!(
+            "\"Not{}A{}Brand\";v=\"{}\"",
+            GREASE_CHARS[v % 11],
+            GREASE_CHARS[(v + 1) % 11],
+            GREASE_VERSIONS[v % 3]
+        );
+        let brands = [
+            grease,
+            format!("\"Chromium\";v=\"{}\"", v),
+            format!("\"{}\";v=\"{}\"", brand, v),
+        ];
+
+        // 第 i 个品牌放到 order[i] 位置
+        let order = ORDERS[v % 6];
+        let mut list = vec![String::new(); 3];
+        for (i, b) in brands.into_iter().enumerate() {
+            list[order[i]] = b;
+        }
+
+        Some(list.join(", "))
+    }
+
+    /// 写入 User-Agent、Accept-Language 和客户端提示头
+    pub fn apply_headers(&self, headers: &mut HeaderMap) {
+        if let Ok(ua) = HeaderValue::from_str(&self.user_agent) {
+            headers.insert(USER_AGENT, ua);
+        }
+        if let Ok(lang) = HeaderValue::from_str(&self.accept_language) {
+            headers.insert(ACCEPT_LANGUAGE, lang);
+        }
+
+        if let Some(brands) = self.sec_ch_ua().and_then(|b| HeaderValue::from_str(&b).ok()) {
+            headers.insert("sec-ch-ua", brands);
+            headers.insert("sec-ch-ua-mobile", HeaderValue::from_static("?0"));
+            if let Ok(platform) =
+                HeaderValue::from_str(&format!("\"{}\"", self.os.platform_name()))
+            {
+                headers.insert("sec-ch-ua-platform", platform);
+            }
+        }
+
+        // Brave 默认开启 GPC
+        if self.browser == BrowserKind::Brave {
+            headers.insert("sec-gpc", HeaderValue::from_static("1"));
+        }
+    }
+}
+
+fn build_user_agent(browser: BrowserKind, version: u32, os: OsKind, safari_minor: u32) -> String {
+    let platform = match (browser, os) {
+        (BrowserKind::Firefox, OsKind::Windows) => {
+            format!("Windows NT 10.0; Win64; x64; rv:{}.0", version)
+        }
+        (BrowserKind::Firefox, OsKind::MacOs) => {
+            format!("Macintosh; Intel Mac OS X 10.15; rv:{}.0", version)
+        }
+        (BrowserKind::Firefox, OsKind::Linux) => format!("X11; Linux x86_64; rv:{}.0", version),
+        (_, OsKind::Windows) => "Windows NT 10.0; Win64; x64".to_string(),
+        (_, OsKind::MacOs) => "Macintosh; Intel Mac OS X 10_15_7".to_string(),
+        (_, OsKind::Linux) => "X11; Linux x86_64".to_string(),
+    };
+
+    match browser {
+        BrowserKind::Chrome | BrowserKind::Brave => format!(
+            "Mozilla/5.0 ({}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{}.0.0.0 Safari/537.36",
+            platform, version
+        ),
+        BrowserKind::Edge => format!(
+            "Mozilla/5.0 ({}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{}.0.0.0 Safari/537.36 Edg/{}.0.0.0",
+            platform, version, version
+        ),
+        BrowserKind::Firefox => format!(
+            "Mozilla/5.0 ({}) Gecko/20100101 Firefox/{}.0",
+            platform, version
+        ),
+        BrowserKind::Safari => format!(
+            "Mozilla/5.0 ({}) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/{}.{} Safari/605.1.15",
+            platform, version, safari_minor
+        ),
+    }
+}
+
+fn build_accept_language(browser: BrowserKind, locale: &str) -> String {
+    let lang = locale.split('-').next().unwrap_or(locale);
+    let q = match browser {
+        BrowserKind::Firefox => "0.5",
+        _ => "0.9",
+    };
+
+    if lang == "en" {
+        format!("{},en;q={}", locale, q)
+    } else {
+        format!("{},{};q={},en-US;q=0.8,en;q=0.7", locale, lang, q)
+    }
+}
@@ -1,26 +1,117 @@
-use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT, ACCEPT, ACCEPT_LANGUAGE};
-use reqwest::Client;
+use lazy_static::lazy_static;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, RETRY_AFTER};
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use rand::Rng;
 
-pub fn create_client(proxy: Option<String>) -> Result<Client, String> {
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use super::cookies::CookieJar;
+use super::fingerprint::BrowserProfile;
+#[cfg(feature = "impersonate")]
+use super::impersonate;
+use super::proxy_health::{self, ProxyFailureKind};
+use super::rate_limit;
+use super::recorder::{self, ReplayOutcome};
+use super::traffic;
+
+/// 代理凭据模板占位符（住宅代理通常把会话ID和国家编码进用户名）
+pub const SESSION_PLACEHOLDER: &str = "{session}";
+pub const COUNTRY_PLACEHOLDER: &str = "{country}";
+
+/// 展开代理模板所需的会话参数
+#[derive(Debug, Clone, Default)]
+pub struct ProxySession {
+    /// 会话ID，None 时每次建客户端都生成新的
+    pub session_id: Option<String>,
+    pub country: Option<String>,
+    /// 轮换模式：每个任务开始时由 `HttpClient::rotate_session` 换新会话
+    pub rotate: bool,
+}
+
+/// 生成随机会话ID（小写字母+数字）
+pub fn generate_session_id() -> String {
+    const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+    let mut rng = rand::thread_rng();
+    (0..10)
+        .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+        .collect()
+}
+
+/// 展开代理URL中的 {session} / {country} 占位符
+pub fn expand_proxy_template(proxy_url: &str, session: &ProxySession) -> String {
+    let mut url = proxy_url.to_string();
+
+    if url.contains(SESSION_PLACEHOLDER) {
+        let session_id = session
+            .session_id
+            .clone()
+            .unwrap_or_else(generate_session_id);
+        url = url.replace(SESSION_PLACEHOLDER, &session_id);
+    }
+
+    if url.contains(COUNTRY_PLACEHOLDER) {
+        url = match session.country.as_deref().filter(|c| !c.is_empty()) {
+            Some(country) => url.replace(COUNTRY_PLACEHOLDER, &country.to_lowercase()),
+            None => strip_country_segment(&url),
+        };
+    }
+
+    url
+}
+
+/// 没有国家时去掉模板中的国家段（如 `-country-{country}`、`_{country}`），
+/// 避免生成 `user-country-` 这类会被网关拒绝的用户名
+fn strip_country_segment(url: &str) -> String {
+    let mut url = url.to_string();
+    while let Some(pos) = url.find(COUNTRY_PLACEHOLDER) {
+        let tail = url[pos + COUNTRY_PLACEHOLDER.len()..].to_string();
+        let head = &url[..pos];
+        let start = match head.strip_suffix(['-', '_']) {
+            // 占位符前为 "<分隔符><键><分隔符>" 时连同键一起去掉
+            Some(rest) => match rest.rfind(['-', '_', ':', '/', '@']) {
+                Some(i) if rest[i..].starts_with(['-', '_']) => i,
+                _ => rest.len(),
+            },
+            None => pos,
+        };
+        url = format!("{}{}", &url[..start], tail);
+    }
+    url
+}
+
+/// 客户端默认请求头（Accept + 指纹相关头）
+pub fn default_headers(profile: &BrowserProfile) -> HeaderMap {
     let mut headers = HeaderMap::new();
-    // Default headers
     headers.insert(ACCEPT, HeaderValue::from_static("application/json, text/plain, */*"));
-    headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.8"));
-    
-    // Random user agent
-    let user_agents = [
-        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
-        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
-        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36",
-    ];
-    let ua_idx = rand::random::<usize>() % user_agents.len();
-    headers.insert(USER_AGENT, HeaderValue::from_str(user_agents[ua_idx]).unwrap());
-
-    let client_builder = Client::builder()
-        .default_headers(headers);
-        
+    profile.apply_headers(&mut headers);
+    headers
+}
+
+/// 创建客户端（每次随机生成一份指纹，用于不归属任何钱包的请求）
+pub fn create_client(proxy: Option<String>) -> Result<Client, String> {
+    create_client_with_session(proxy, &ProxySession::default(), &BrowserProfile::generate(), None)
+}
+
+/// 创建客户端，传入 cookie 罐时自动携带并保存 cookie
+pub fn create_client_with_session(
+    proxy: Option<String>,
+    session: &ProxySession,
+    profile: &BrowserProfile,
+    cookies: Option<Arc<CookieJar>>,
+) -> Result<Client, String> {
+    let mut client_builder = Client::builder()
+        .default_headers(default_headers(profile));
+
+    if let Some(jar) = cookies {
+        client_builder = client_builder.cookie_provider(jar);
+    }
+
     let client = if let Some(proxy_url) = proxy {
         if !proxy_url.is_empty() {
+             let proxy_url = expand_proxy_template(&proxy_url, session);
              client_builder.proxy(reqwest::Proxy::all(proxy_url).map_err(|e| e.to_string())?)
         } else {
             client_builder
@@ -28,6 +119,703 @@ pub fn create_client(proxy: Option<String>) -> Result<Client, String> {
     } else {
         client_builder
     };
-    
+
     client.build().map_err(|e| e.to_string())
 }
+
+/// 请求归属（代理 / 钱包 / 脚本运行），用于流量统计
+#[derive(Debug, Clone, Default)]
+pub struct RequestTags {
+    pub proxy_id: Option<String>,
+    pub wallet: Option<String>,
+    pub run_id: Option<String>,
+}
+
+/// 请求失败的分类
+#[derive(Debug, Clone)]
+pub enum HttpError {
+    /// 连接、超时、代理等传输层错误
+    Transport(String),
+    /// 服务端返回非 2xx 状态码
+    Status { status: StatusCode, body: String },
+    /// 响应体无法解析
+    Decode(String),
+    /// 接口返回了业务错误码
+    Api { code: i64, message: String },
+    /// 回放模式下没有匹配的录制
+    Replay(String),
+}
+
+impl HttpError {
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transport(msg) => write!(f, "{}", msg),
+            Self::Status { status, body } => {
+                let body: String = body.chars().take(200).collect();
+                write!(f, "HTTP {}: {}", status, body)
+            }
+            Self::Decode(msg) => write!(f, "Failed to parse response: {}", msg),
+            Self::Api { code, message } => write!(f, "API error {}: {}", code, message),
+            Self::Replay(msg) => write!(f, "Replay: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+impl From<HttpError> for String {
+    fn from(e: HttpError) -> Self {
+        e.to_string()
+    }
+}
+
+/// 带业务错误码的接口响应
+pub trait ApiResponse {
+    fn api_code(&self) -> i64;
+    fn api_message(&self) -> String;
+}
+
+/// 已完整读取的响应
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, HttpError> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| HttpError::Decode(format!("{} - Raw: {}", e, self.text())))
+    }
+
+    /// 解析接口响应，业务码非 0 时返回 `HttpError::Api`
+    pub fn api_json<T: serde::de::DeserializeOwned + ApiResponse>(&self) -> Result<T, HttpError> {
+        let data: T = self.json()?;
+        match data.api_code() {
+            0 => Ok(data),
+            code => Err(HttpError::Api {
+                code,
+                message: data.api_message(),
+            }),
+        }
+    }
+}
+
+/// 重试策略：指数退避 + 随机抖动，429/503 优先使用 Retry-After
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Retry-After 超过该值时不再等待，直接失败
+    pub max_retry_after_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 500,
+            max_delay_ms: 10_000,
+            max_retry_after_secs: 120,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 第 attempt 次重试前的等待时间（从 0 开始）
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay_ms
+            .saturating_mul(1u64 << attempt.min(16))
+            .min(self.max_delay_ms);
+        // equal jitter：在 [exp/2, exp] 内随机，保留一半的指数退避
+        let delay = rand::thread_rng().gen_range(exp / 2..=exp.max(1));
+        Duration::from_millis(delay)
+    }
+}
+
+lazy_static! {
+    static ref DEFAULT_RETRY: RwLock<RetryPolicy> = RwLock::new(RetryPolicy::default());
+}
+
+/// 设置全局默认重试策略（客户端未单独设置时使用）
+pub fn set_default_retry_policy(policy: RetryPolicy) {
+    *DEFAULT_RETRY.write().unwrap() = policy;
+}
+
+pub fn default_retry_policy() -> RetryPolicy {
+    DEFAULT_RETRY.read().unwrap().clone()
+}
+
+/// 只有安全方法默认完整重试；POST 等可能产生副作用的请求需调用方显式声明幂等
+fn is_idempotent_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status.is_server_error()
+}
+
+/// 解析 Retry-After（秒数或 HTTP 日期）
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let secs = at
+        .with_timezone(&chrono::Utc)
+        .signed_duration_since(chrono::Utc::now())
+        .num_seconds();
+    Some(Duration::from_secs(secs.max(0) as u64))
+}
+
+/// 客户端使用的代理（未展开的模板 + 会话），按需创建指纹模拟客户端时复用
+#[derive(Debug, Clone, Default)]
+pub struct ProxyConfig {
+    pub url: Option<String>,
+    pub session: ProxySession,
+}
+
+/// 备用代理解析结果
+pub struct FallbackClient {
+    pub client: Client,
+    pub proxy_id: String,
+    pub proxy: ProxyConfig,
+}
+
+/// 代理失效时的备用代理解析器：参数为失效代理ID
+pub type ProxyFallback = Arc<dyn Fn(&str) -> Option<FallbackClient> + Send + Sync>;
+
+/// 传输层返回的原始响应，响应体读取失败时 body 为 Err
+pub(crate) struct RawResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Result<Vec<u8>, String>,
+}
+
+/// 一次发送的结果
+struct Attempt {
+    result: Result<HttpResponse, HttpError>,
+    /// 代理是否因本次失败被隔离
+    newly_quarantined: bool,
+    /// 请求是否可能已到达服务端（连接失败、发送前拒绝等为 false，可安全重发）
+    maybe_sent: bool,
+}
+
+/// 传输层错误，kind 为能归因于代理的失败类型
+pub(crate) struct TransportFailure {
+    pub message: String,
+    pub kind: Option<ProxyFailureKind>,
+}
+
+struct ActiveClient {
+    client: Client,
+    tags: RequestTags,
+    proxy: ProxyConfig,
+    #[cfg(feature = "impersonate")]
+    impersonated: Option<wreq::Client>,
+}
+
+/// 带归属标签的 HTTP 客户端，所有请求经由 `send` 统一处理
+///
+/// 克隆共享同一底层状态，切换备用代理后所有克隆同时生效
+#[derive(Clone)]
+pub struct HttpClient {
+    active: Arc<RwLock<ActiveClient>>,
+    fallback: Option<ProxyFallback>,
+    profile: Option<Arc<BrowserProfile>>,
+    impersonate_hosts: Option<Arc<HashSet<String>>>,
+    retry: Option<RetryPolicy>,
+    cookies: Option<Arc<CookieJar>>,
+}
+
+impl HttpClient {
+    pub fn new(client: Client, tags: RequestTags) -> Self {
+        Self {
+            active: Arc::new(RwLock::new(ActiveClient {
+                client,
+                tags,
+                proxy: ProxyConfig::default(),
+                #[cfg(feature = "impersonate")]
+                impersonated: None,
+            })),
+            fallback: None,
+            profile: None,
+            impersonate_hosts: None,
+            retry: None,
+            cookies: None,
+        }
+    }
+
+    /// 为该客户端单独设置重试策略
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// 记录客户端使用的浏览器指纹（供需要时区/屏幕等信息的接口读取）
+    pub fn with_profile(mut self, profile: BrowserProfile) -> Self {
+        self.profile = Some(Arc::new(profile));
+        self
+    }
+
+    pub fn profile(&self) -> Option<&BrowserProfile> {
+        self.profile.as_deref()
+    }
+
+    /// 记录底层客户端使用的 cookie 罐（模拟传输不经过 reqwest，需要单独读写）
+    pub fn with_cookies(mut self, jar: Arc<CookieJar>) -> Self {
+        self.cookies = Some(jar);
+        self
+    }
+
+    pub fn cookies(&self) -> Option<&CookieJar> {
+        self.cookies.as_deref()
+    }
+
+    /// 记录底层客户端使用的代理
+    pub fn with_proxy(self, proxy: ProxyConfig) -> Self {
+        self.active.write().unwrap().proxy = proxy;
+        self
+    }
+
+    /// 对指定域名的请求模拟浏览器 TLS/HTTP2 指纹（与钱包指纹的浏览器一致）
+    ///
+    /// 请求代码无需改动；未启用 `impersonate` feature 或未设置指纹时仍走普通传输
+    pub fn with_impersonation(mut self, hosts: &[&str]) -> Self {
+        let mut set = self
+            .impersonate_hosts
+            .as_deref()
+            .cloned()
+            .unwrap_or_default();
+        set.extend(hosts.iter().map(|h| h.to_lowercase()));
+        self.impersonate_hosts = Some(Arc::new(set));
+        self
+    }
+
+    /// 轮换模式的代理换一个新会话（重建底层客户端，cookie 罐与指纹不变）
+    ///
+    /// 非轮换模式或代理模板中没有 {session} 时不做任何事
+    pub fn rotate_session(&self) -> Result<(), String> {
+        let mut active = self.active.write().unwrap();
+        let rotatable = active.proxy.session.rotate
+            && active
+                .proxy
+                .url
+                .as_deref()
+                .is_some_and(|url| url.contains(SESSION_PLACEHOLDER));
+        if !rotatable {
+            return Ok(());
+        }
+
+        // 固定新会话ID，指纹模拟客户端按需重建时使用同一会话
+        let mut session = active.proxy.session.clone();
+        session.session_id = Some(generate_session_id());
+        let profile = match &self.profile {
+            Some(profile) => (**profile).clone(),
+            None => BrowserProfile::generate(),
+        };
+        active.client = create_client_with_session(
+            active.proxy.url.clone(),
+            &session,
+            &profile,
+            self.cookies.clone(),
+        )?;
+        active.proxy.session = session;
+        #[cfg(feature = "impersonate")]
+        {
+            active.impersonated = None;
+        }
+        Ok(())
+    }
+
+    /// 设置代理失效时的备用代理解析器
+    pub fn with_fallback(mut self, fallback: ProxyFallback) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    pub fn tags(&self) -> RequestTags {
+        self.active.read().unwrap().tags.clone()
+    }
+
+    /// 客户端当前使用的代理（切换备用代理或轮换会话后随之变化）
+    pub fn proxy(&self) -> ProxyConfig {
+        self.active.read().unwrap().proxy.clone()
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.active.read().unwrap().client.get(url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.active.read().unwrap().client.post(url)
+    }
+
+    /// 发送请求并读取完整响应，同时记录流量和代理健康状况
+    ///
+    /// GET/HEAD/OPTIONS 的传输错误和 429/5xx 按重试策略退避重发；其它方法只在请求
+    /// 确定未发出（如连接失败）时重发，避免签到、领水等接口重复提交。
+    /// 目标域名配置了限速时先取令牌。
+    /// 代理因失败被隔离且配置了备用代理时，切换后重发（不计入重试次数）。
+    /// 最终非 2xx 响应返回 `HttpError::Status`
+    pub async fn send(&self, request: RequestBuilder) -> Result<HttpResponse, HttpError> {
+        self.send_with(request, false).await
+    }
+
+    /// 按幂等请求发送：任何方法都按完整重试策略重发
+    ///
+    /// 仅用于重复提交没有副作用的接口（查询类 POST、JSON-RPC 等）
+    pub async fn send_idempotent(&self, request: RequestBuilder) -> Result<HttpResponse, HttpError> {
+        self.send_with(request, true).await
+    }
+
+    async fn send_with(
+        &self,
+        request: RequestBuilder,
+        idempotent: bool,
+    ) -> Result<HttpResponse, HttpError> {
+        let request = request
+            .build()
+            .map_err(|e| HttpError::Transport(format!("Invalid request: {}", e)))?;
+        let idempotent = idempotent || is_idempotent_method(request.method());
+        let host = request.url().host_str().unwrap_or_default().to_string();
+        let policy = self.retry.clone().unwrap_or_else(default_retry_policy);
+        // 回放时不限速、不等待、不切换代理，只按录制重现结果
+        let replaying = recorder::is_replaying();
+
+        let mut template = Some(request);
+        let mut attempt = 0;
+        let mut switched = false;
+
+        loop {
+            let current = template.take().expect("request template");
+            // 请求体不可克隆时只能发送一次
+            template = current.try_clone();
+
+            if !replaying {
+                rate_limit::acquire(&host).await;
+            }
+            let Attempt {
+                result,
+                newly_quarantined,
+                maybe_sent,
+            } = self.send_once(current).await;
+
+            // 切换代理重发同样只用于幂等请求或未发出的请求
+            if !replaying
+                && (newly_quarantined || self.current_proxy_quarantined())
+                && (idempotent || !maybe_sent)
+                && !switched
+                && template.is_some()
+                && self.switch_to_fallback()
+            {
+                switched = true;
+                continue;
+            }
+
+            let wait = match &result {
+                Ok(response) if response.status.is_success() => None,
+                Ok(response) if idempotent && is_retryable_status(response.status) => {
+                    match retry_after(&response.headers) {
+                        Some(d) if d.as_secs() > policy.max_retry_after_secs => None,
+                        Some(d) => Some(d),
+                        None => Some(policy.backoff(attempt)),
+                    }
+                }
+                Ok(_) => None,
+                Err(HttpError::Transport(_)) if idempotent || !maybe_sent => {
+                    Some(policy.backoff(attempt))
+                }
+                Err(_) => None,
+            };
+
+            match wait {
+                Some(delay) if attempt < policy.max_retries && template.is_some() => {
+                    attempt += 1;
+                    if !replaying {
+                        tokio::time::sleep(delay).await;
+                    }
+                }
+                _ => {
+                    return match result {
+                        Ok(response) if !response.status.is_success() => Err(HttpError::Status {
+                            status: response.status,
+                            body: response.text(),
+                        }),
+                        other => other,
+                    };
+                }
+            }
+        }
+    }
+
+    fn current_proxy_quarantined(&self) -> bool {
+        match &self.active.read().unwrap().tags.proxy_id {
+            Some(id) => proxy_health::is_quarantined(id),
+            None => false,
+        }
+    }
+
+    /// 切换到备用代理，成功返回 true
+    fn switch_to_fallback(&self) -> bool {
+        let fallback = match &self.fallback {
+            Some(f) => f,
+            None => return false,
+        };
+
+        let failed_id = match self.active.read().unwrap().tags.proxy_id.clone() {
+            Some(id) => id,
+            None => return false,
+        };
+
+        match fallback(&failed_id) {
+            Some(next) => {
+                let mut active = self.active.write().unwrap();
+                active.client = next.client;
+                active.tags.proxy_id = Some(next.proxy_id);
+                active.proxy = next.proxy;
+                #[cfg(feature = "impersonate")]
+                {
+                    active.impersonated = None;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 该请求需要指纹模拟时返回所用指纹
+    #[cfg(feature = "impersonate")]
+    fn impersonation_profile(&self, url: &reqwest::Url) -> Option<Arc<BrowserProfile>> {
+        let host = url.host_str()?.to_lowercase();
+        if self.impersonate_hosts.as_ref()?.contains(&host) {
+            self.profile.clone()
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "impersonate")]
+    async fn execute_impersonated(
+        &self,
+        profile: &BrowserProfile,
+        request: reqwest::Request,
+    ) -> Result<RawResponse, TransportFailure> {
+        // 首次使用时按当前代理创建，切换备用代理后重建
+        let client = {
+            let mut active = self.active.write().unwrap();
+            match &active.impersonated {
+                Some(c) => c.clone(),
+                None => {
+                    let c = impersonate::build_client(profile, &active.proxy).map_err(|e| {
+                        TransportFailure {
+                            message: e,
+                            kind: None,
+                        }
+                    })?;
+                    active.impersonated = Some(c.clone());
+                    c
+                }
+            }
+        };
+
+        impersonate::execute(&client, profile, self.cookies.as_deref(), request).await
+    }
+
+    /// 发送一次请求，返回结果、代理是否因此被隔离以及请求是否可能已发出
+    async fn send_once(&self, request: reqwest::Request) -> Attempt {
+        let (client, tags) = {
+            let active = self.active.read().unwrap();
+            (active.client.clone(), active.tags.clone())
+        };
+
+        if recorder::is_replaying() {
+            return Attempt {
+                result: replay_response(&tags, &request),
+                newly_quarantined: false,
+                maybe_sent: true,
+            };
+        }
+
+        if let Some(proxy_id) = &tags.proxy_id {
+            if traffic::is_proxy_paused(proxy_id) {
+                return Attempt {
+                    result: Err(HttpError::Transport(format!(
+                        "Proxy {} paused: traffic budget exceeded",
+                        proxy_id
+                    ))),
+                    newly_quarantined: false,
+                    maybe_sent: false,
+                };
+            }
+            if proxy_health::is_quarantined(proxy_id) {
+                return Attempt {
+                    result: Err(HttpError::Transport(format!("Proxy {} quarantined", proxy_id))),
+                    newly_quarantined: false,
+                    maybe_sent: false,
+                };
+            }
+        }
+
+        let bytes_sent = traffic::request_size(&request);
+        let recording = recorder::capture(&tags, &request);
+
+        #[cfg(feature = "impersonate")]
+        let outcome = match self.impersonation_profile(request.url()) {
+            Some(profile) => self.execute_impersonated(&profile, request).await,
+            None => execute_plain(&client, request).await,
+        };
+        #[cfg(not(feature = "impersonate"))]
+        let outcome = execute_plain(&client, request).await;
+
+        if let Some(pending) = recording {
+            recorder::finish(pending, &outcome);
+        }
+
+        let response = match outcome {
+            Ok(r) => r,
+            Err(failure) => {
+                traffic::record(&tags, bytes_sent, 0);
+                let quarantined = match (&tags.proxy_id, failure.kind) {
+                    (Some(id), Some(kind)) => proxy_health::record_failure(id, kind),
+                    _ => false,
+                };
+                return Attempt {
+                    result: Err(HttpError::Transport(format!(
+                        "Request failed: {}",
+                        failure.message
+                    ))),
+                    newly_quarantined: quarantined,
+                    maybe_sent: failure.kind != Some(ProxyFailureKind::Connect),
+                };
+            }
+        };
+
+        let RawResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        let body_len = body.as_ref().map_or(0, |b| b.len());
+        traffic::record(
+            &tags,
+            bytes_sent,
+            traffic::response_size(&headers, body_len),
+        );
+
+        if let Some(proxy_id) = &tags.proxy_id {
+            if status == StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+                let quarantined =
+                    proxy_health::record_failure(proxy_id, ProxyFailureKind::AuthRequired);
+                // 代理拒绝认证时请求不会被转发
+                return Attempt {
+                    result: Err(HttpError::Transport(format!(
+                        "Proxy {} authentication failed (407)",
+                        proxy_id
+                    ))),
+                    newly_quarantined: quarantined,
+                    maybe_sent: false,
+                };
+            }
+            proxy_health::record_success(proxy_id);
+        }
+
+        let body = match body {
+            Ok(b) => b,
+            Err(e) => {
+                return Attempt {
+                    result: Err(HttpError::Transport(format!("Failed to read response: {}", e))),
+                    newly_quarantined: false,
+                    maybe_sent: true,
+                }
+            }
+        };
+
+        Attempt {
+            result: Ok(HttpResponse {
+                status,
+                headers,
+                body,
+            }),
+            newly_quarantined: false,
+            maybe_sent: true,
+        }
+    }
+}
+
+/// 回放模式：从录制中取响应，不经过网络
+fn replay_response(tags: &RequestTags, request: &reqwest::Request) -> Result<HttpResponse, HttpError> {
+    match recorder::replay(tags, request) {
+        ReplayOutcome::Response(RawResponse {
+            status,
+            headers,
+            body,
+        }) => {
+            let body = body
+                .map_err(|e| HttpError::Transport(format!("Failed to read response: {}", e)))?;
+            Ok(HttpResponse {
+                status,
+                headers,
+                body,
+            })
+        }
+        ReplayOutcome::Failure(e) => Err(HttpError::Transport(format!("Request failed: {}", e))),
+        ReplayOutcome::Missing(e) => Err(HttpError::Replay(e)),
+    }
+}
+
+/// 普通 reqwest 传输
+async fn execute_plain(
+    client: &Client,
+    request: reqwest::Request,
+) -> Result<RawResponse, TransportFailure> {
+    let response = client.execute(request).await.map_err(|e| {
+        let kind = if e.is_timeout() {
+            Some(ProxyFailureKind::Timeout)
+        } else if e.is_connect() {
+            Some(ProxyFailureKind::Connect)
+        } else {
+            None
+        };
+        TransportFailure {
+            message: e.to_string(),
+            kind,
+        }
+    })?;
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response
+        .bytes()
+        .await
+        .map(|b| b.to_vec())
+        .map_err(|e| e.to_string());
+
+    Ok(RawResponse {
+        status,
+        headers,
+        body,
+    })
+}
@@ -0,0 +1,133 @@
+use super::cookies::CookieJar;
+use super::fingerprint::{BrowserKind, BrowserProfile, OsKind};
+use super::http::{default_headers, expand_proxy_template, ProxyConfig, RawResponse, TransportFailure};
+use super::proxy_health::ProxyFailureKind;
+use reqwest::cookie::CookieStore;
+use reqwest::header::{COOKIE, SET_COOKIE};
+use wreq_util::{Emulation, EmulationOS, EmulationOption};
+
+/// 各浏览器可模拟的版本（按版本升序）
+const CHROME_PROFILES: &[(u32, Emulation)] = &[
+    (131, Emulation::Chrome131),
+    (132, Emulation::Chrome132),
+    (133, Emulation::Chrome133),
+    (134, Emulation::Chrome134),
+    (135, Emulation::Chrome135),
+    (136, Emulation::Chrome136),
+    (137, Emulation::Chrome137),
+];
+
+const EDGE_PROFILES: &[(u32, Emulation)] = &[(131, Emulation::Edge131), (134, Emulation::Edge134)];
+
+const FIREFOX_PROFILES: &[(u32, Emulation)] = &[
+    (133, Emulation::Firefox133),
+    (135, Emulation::Firefox135),
+    (136, Emulation::Firefox136),
+    (139, Emulation::Firefox139),
+];
+
+const SAFARI_PROFILES: &[(u32, Emulation)] =
+    &[(17, Emulation::Safari17_5), (18, Emulation::Safari18_5)];
+
+/// 取不高于目标版本的最近一项，没有则取最旧的
+fn nearest(profiles: &[(u32, Emulation)], version: u32) -> Emulation {
+    profiles
+        .iter()
+        .rev()
+        .find(|(v, _)| *v <= version)
+        .unwrap_or(&profiles[0])
+        .1
+}
+
+/// 钱包指纹对应的 TLS/HTTP2 模拟配置（Brave 的握手与同版本 Chrome 一致）
+pub fn emulation_for(profile: &BrowserProfile) -> EmulationOption {
+    let version = profile.browser_version;
+    let target = match profile.browser {
+        BrowserKind::Chrome | BrowserKind::Brave => nearest(CHROME_PROFILES, version),
+        BrowserKind::Edge => nearest(EDGE_PROFILES, version),
+        BrowserKind::Firefox => nearest(FIREFOX_PROFILES, version),
+        BrowserKind::Safari => nearest(SAFARI_PROFILES, version),
+    };
+
+    let os = match profile.os {
+        OsKind::Windows => EmulationOS::Windows,
+        OsKind::MacOs => EmulationOS::MacOS,
+        OsKind::Linux => EmulationOS::Linux,
+    };
+
+    EmulationOption::builder()
+        .emulation(target)
+        .emulation_os(os)
+        .build()
+}
+
+/// 创建模拟浏览器握手的客户端，代理与普通客户端相同
+pub fn build_client(profile: &BrowserProfile, proxy: &ProxyConfig) -> Result<wreq::Client, String> {
+    let mut builder = wreq::Client::builder().emulation(emulation_for(profile));
+
+    if let Some(url) = proxy.url.as_deref().filter(|u| !u.is_empty()) {
+        let url = expand_proxy_template(url, &proxy.session);
+        builder = builder.proxy(wreq::Proxy::all(url.as_str()).map_err(|e| e.to_string())?);
+    }
+
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// 通过模拟客户端发送已构建好的 reqwest 请求，cookie 与普通传输共用同一个罐
+pub(crate) async fn execute(
+    client: &wreq::Client,
+    profile: &BrowserProfile,
+    cookies: Option<&CookieJar>,
+    request: reqwest::Request,
+) -> Result<RawResponse, TransportFailure> {
+    // 指纹头打底，请求自身的头优先
+    let mut headers = default_headers(profile);
+    for (name, value) in request.headers() {
+        headers.insert(name.clone(), value.clone());
+    }
+    if let Some(value) = cookies.and_then(|jar| jar.cookies(request.url())) {
+        headers.insert(COOKIE, value);
+    }
+
+    let mut builder = client
+        .request(request.method().clone(), request.url().as_str())
+        .headers(headers);
+
+    if let Some(timeout) = request.timeout() {
+        builder = builder.timeout(*timeout);
+    }
+    if let Some(body) = request.body().and_then(|b| b.as_bytes()) {
+        builder = builder.body(body.to_vec());
+    }
+
+    let response = builder.send().await.map_err(|e| {
+        let kind = if e.is_timeout() {
+            Some(ProxyFailureKind::Timeout)
+        } else if e.is_connect() {
+            Some(ProxyFailureKind::Connect)
+        } else {
+            None
+        };
+        TransportFailure {
+            message: e.to_string(),
+            kind,
+        }
+    })?;
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    if let Some(jar) = cookies {
+        jar.set_cookies(&mut headers.get_all(SET_COOKIE).iter(), request.url());
+    }
+    let body = response
+        .bytes()
+        .await
+        .map(|b| b.to_vec())
+        .map_err(|e| e.to_string());
+
+    Ok(RawResponse {
+        status,
+        headers,
+        body,
+    })
+}
@@ -1,2 +1,11 @@
 pub mod http;
-pub mod capsolver;
+pub mod captcha;
+pub mod traffic;
+pub mod rpc;
+pub mod proxy_health;
+pub mod rate_limit;
+pub mod fingerprint;
+pub mod cookies;
+pub mod recorder;
+#[cfg(feature = "impersonate")]
+pub mod impersonate;
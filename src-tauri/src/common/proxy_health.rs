@@ -0,0 +1,97 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref HEALTH: Mutex<HealthState> = Mutex::new(HealthState::default());
+}
+
+/// 连续失败达到该次数后隔离代理
+pub const QUARANTINE_THRESHOLD: u32 = 3;
+
+/// 代理失败类型（只统计能归因于代理本身的错误）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyFailureKind {
+    Connect,      // 连接失败
+    Timeout,      // 超时
+    AuthRequired, // HTTP 407
+}
+
+/// 待落盘的健康事件，kind 为 None 表示失败计数被成功请求清零
+#[derive(Debug, Clone)]
+pub struct ProxyHealthEvent {
+    pub proxy_id: String,
+    pub kind: Option<ProxyFailureKind>,
+    pub at: String,
+}
+
+#[derive(Default)]
+struct HealthState {
+    consecutive: HashMap<String, u32>,
+    events: Vec<ProxyHealthEvent>,
+    quarantined: HashSet<String>,
+}
+
+/// 记录一次代理失败，返回该代理是否因此被隔离
+pub fn record_failure(proxy_id: &str, kind: ProxyFailureKind) -> bool {
+    let mut state = HEALTH.lock().unwrap();
+
+    let count = state.consecutive.entry(proxy_id.to_string()).or_insert(0);
+    *count += 1;
+    let reached = *count >= QUARANTINE_THRESHOLD;
+
+    state.events.push(ProxyHealthEvent {
+        proxy_id: proxy_id.to_string(),
+        kind: Some(kind),
+        at: chrono::Utc::now().to_rfc3339(),
+    });
+
+    if reached {
+        state.quarantined.insert(proxy_id.to_string())
+    } else {
+        false
+    }
+}
+
+/// 记录一次成功请求（清零连续失败计数）
+pub fn record_success(proxy_id: &str) {
+    let mut state = HEALTH.lock().unwrap();
+
+    if let Some(count) = state.consecutive.get_mut(proxy_id) {
+        if *count > 0 {
+            *count = 0;
+            state.events.push(ProxyHealthEvent {
+                proxy_id: proxy_id.to_string(),
+                kind: None,
+                at: chrono::Utc::now().to_rfc3339(),
+            });
+        }
+    }
+}
+
+/// 取出所有未落盘的事件
+pub fn take_events() -> Vec<ProxyHealthEvent> {
+    std::mem::take(&mut HEALTH.lock().unwrap().events)
+}
+
+/// 落盘失败时放回取出的事件（排在之后产生的事件前面）
+pub fn restore_events(mut events: Vec<ProxyHealthEvent>) {
+    let mut state = HEALTH.lock().unwrap();
+    events.append(&mut state.events);
+    state.events = events;
+}
+
+pub fn is_quarantined(proxy_id: &str) -> bool {
+    HEALTH.lock().unwrap().quarantined.contains(proxy_id)
+}
+
+/// 用持久化状态覆盖隔离集合（启动时 / 复检后）
+pub fn set_quarantined(ids: HashSet<String>) {
+    let mut state = HEALTH.lock().unwrap();
+    for id in state.quarantined.difference(&ids).cloned().collect::<Vec<_>>() {
+        state.consecutive.remove(&id);
+    }
+    state.quarantined = ids;
+}
@@ -0,0 +1,108 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+lazy_static! {
+    static ref BUCKETS: Mutex<HashMap<String, TokenBucket>> = Mutex::new(HashMap::new());
+}
+
+/// 单个域名的限速配置（所有钱包共享同一个令牌桶）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HostRateLimit {
+    pub host: String,
+    pub requests_per_sec: f64,
+    pub burst: u32,
+}
+
+struct TokenBucket {
+    limit: HostRateLimit,
+    /// 用户配置的限速不会被脚本默认值覆盖
+    explicit: bool,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: HostRateLimit, explicit: bool) -> Self {
+        Self {
+            tokens: limit.burst.max(1) as f64,
+            limit,
+            explicit,
+            updated: Instant::now(),
+        }
+    }
+
+    /// 取一个令牌，不足时返回需要等待的时间
+    fn try_take(&mut self) -> Option<Duration> {
+        let now = Instant::now();
+        let capacity = self.limit.burst.max(1) as f64;
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.requests_per_sec).min(capacity);
+        self.updated = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            let missing = 1.0 - self.tokens;
+            Some(Duration::from_secs_f64(missing / self.limit.requests_per_sec))
+        }
+    }
+}
+
+/// 用用户配置替换全部限速（未出现在配置中的脚本默认值保留）
+pub fn set_limits(limits: Vec<HostRateLimit>) {
+    let mut buckets = BUCKETS.lock().unwrap();
+    buckets.retain(|_, b| !b.explicit);
+
+    for limit in limits.into_iter().filter(|l| l.requests_per_sec > 0.0) {
+        let host = limit.host.to_lowercase();
+        buckets.insert(host, TokenBucket::new(limit, true));
+    }
+}
+
+/// 脚本注册默认限速，用户已配置该域名时不生效
+pub fn ensure_limit(host: &str, requests_per_sec: f64, burst: u32) {
+    let host = host.to_lowercase();
+    let mut buckets = BUCKETS.lock().unwrap();
+    if buckets.contains_key(&host) || requests_per_sec <= 0.0 {
+        return;
+    }
+
+    let limit = HostRateLimit {
+        host: host.clone(),
+        requests_per_sec,
+        burst,
+    };
+    buckets.insert(host, TokenBucket::new(limit, false));
+}
+
+/// 当前生效的限速
+pub fn current_limits() -> Vec<HostRateLimit> {
+    let mut limits: Vec<HostRateLimit> = BUCKETS
+        .lock()
+        .unwrap()
+        .values()
+        .map(|b| b.limit.clone())
+        .collect();
+    limits.sort_by(|a, b| a.host.cmp(&b.host));
+    limits
+}
+
+/// 等待目标域名的令牌，未配置限速的域名立即返回
+pub async fn acquire(host: &str) {
+    let host = host.to_lowercase();
+    loop {
+        let wait = match BUCKETS.lock().unwrap().get_mut(&host) {
+            Some(bucket) => bucket.try_take(),
+            None => None,
+        };
+
+        match wait {
+            Some(delay) => tokio::time::sleep(delay).await,
+            None => return,
+        }
+    }
+}
@@ -0,0 +1,718 @@
+use lazy_static::lazy_static;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::http::{RawResponse, RequestTags, TransportFailure};
+
+lazy_static! {
+    static ref STATE: Mutex<RecorderState> = Mutex::new(RecorderState::default());
+}
+
+#[cfg(test)]
+lazy_static! {
+    /// 录制器是全局状态，回放测试与其它发请求的测试需要串行
+    pub(crate) static ref TEST_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+/// 替换敏感值的占位符
+const REDACTED: &str = "[REDACTED]";
+
+/// 名称包含这些片段的请求头 / 查询参数 / JSON 字段会被脱敏（不区分大小写）
+const SENSITIVE_KEYS: &[&str] = &[
+    "authorization",
+    "cookie",
+    "token",
+    "jwt",
+    "signature",
+    "secret",
+    "password",
+    "private",
+    "mnemonic",
+    "apikey",
+    "api_key",
+    "clientkey",
+    "recaptcharesponse",
+];
+
+/// 未归属任何运行的请求写入的录制文件名
+const ADHOC_RECORDING: &str = "adhoc";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecorderMode {
+    /// 正常联网
+    #[default]
+    Off,
+    /// 联网并把请求/响应写入录制文件
+    Record,
+    /// 不联网，按顺序返回录制的响应
+    Replay,
+}
+
+/// 一次请求/响应记录（已脱敏）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedExchange {
+    pub seq: u64,
+    pub recorded_at: String,
+    pub run_id: Option<String>,
+    pub wallet: Option<String>,
+    pub method: String,
+    pub url: String,
+    /// JSON-RPC 请求的方法名（批量请求用逗号连接）
+    pub rpc_method: Option<String>,
+    pub request_headers: Vec<(String, String)>,
+    pub request_body: Option<String>,
+    /// 传输失败时为 None
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub response_headers: Vec<(String, String)>,
+    pub response_body: Option<String>,
+    /// 响应体不是 UTF-8 时以 hex 保存
+    #[serde(default)]
+    pub response_body_hex: bool,
+    /// 传输层错误信息
+    pub error: Option<String>,
+}
+
+impl RecordedExchange {
+    /// 回放匹配键：钱包 + 方法 + 主机路径 + RPC 方法
+    ///
+    /// 查询参数和请求体不参与匹配，签名、随机金额等每次运行不同的值不会导致失配
+    fn replay_key(&self) -> String {
+        let path = self.url.split('?').next().unwrap_or_default();
+        format!(
+            "{}|{}|{}|{}",
+            self.wallet.as_deref().unwrap_or_default(),
+            self.method,
+            path,
+            self.rpc_method.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+/// 录制器状态（回放时用于判断是否完整重现）
+#[derive(Debug, Clone, Serialize)]
+pub struct RecorderStatus {
+    pub mode: RecorderMode,
+    /// 回放中的录制名
+    pub recording: Option<String>,
+    /// 本次录制写入的条数
+    pub recorded: u64,
+    /// 已回放的条数
+    pub served: usize,
+    /// 没有录制可匹配的请求
+    pub missed: Vec<String>,
+    /// 录制中尚未被请求到的条数
+    pub unused: usize,
+}
+
+/// 录制文件信息
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingInfo {
+    pub name: String,
+    pub exchanges: usize,
+    pub size_bytes: u64,
+    pub modified_at: Option<String>,
+}
+
+#[derive(Default)]
+struct RecorderState {
+    mode: RecorderMode,
+    dir: PathBuf,
+    recording: Option<String>,
+    seq: u64,
+    queues: HashMap<String, VecDeque<RecordedExchange>>,
+    served: usize,
+    missed: Vec<String>,
+}
+
+/// 回放结果
+pub(crate) enum ReplayOutcome {
+    Response(RawResponse),
+    /// 录制时即为传输失败
+    Failure(String),
+    /// 没有可匹配的录制
+    Missing(String),
+}
+
+/// 已发出请求的脱敏快照，收到响应后写入录制文件
+pub(crate) struct PendingExchange {
+    exchange: RecordedExchange,
+}
+
+pub fn mode() -> RecorderMode {
+    STATE.lock().unwrap().mode
+}
+
+pub fn is_replaying() -> bool {
+    mode() == RecorderMode::Replay
+}
+
+/// 开始录制，每次运行写入 `dir/<run_id>.jsonl`
+pub fn start_recording(dir: PathBuf) -> Result<(), String> {
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let mut state = STATE.lock().unwrap();
+    *state = RecorderState {
+        mode: RecorderMode::Record,
+        dir,
+        ..Default::default()
+    };
+    Ok(())
+}
+
+/// 开始回放指定录制，返回录制条数
+pub fn start_replay(dir: PathBuf, recording: &str) -> Result<usize, String> {
+    let exchanges = load_recording(&dir, recording)?;
+    let total = exchanges.len();
+
+    let mut queues: HashMap<String, VecDeque<RecordedExchange>> = HashMap::new();
+    for exchange in exchanges {
+        queues.entry(exchange.replay_key()).or_default().push_back(exchange);
+    }
+
+    let mut state = STATE.lock().unwrap();
+    *state = RecorderState {
+        mode: RecorderMode::Replay,
+        dir,
+        recording: Some(recording.to_string()),
+        queues,
+        ..Default::default()
+    };
+    Ok(total)
+}
+
+/// 恢复正常联网，返回结束前的状态
+pub fn stop() -> RecorderStatus {
+    let mut state = STATE.lock().unwrap();
+    let final_status = status_of(&state);
+    *state = RecorderState::default();
+    final_status
+}
+
+pub fn status() -> RecorderStatus {
+    status_of(&STATE.lock().unwrap())
+}
+
+fn status_of(state: &RecorderState) -> RecorderStatus {
+    RecorderStatus {
+        mode: state.mode,
+        recording: state.recording.clone(),
+        recorded: state.seq,
+        served: state.served,
+        missed: state.missed.clone(),
+        unused: state.queues.values().map(|q| q.len()).sum(),
+    }
+}
+
+fn recording_path(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(format!("无效的录制名: {}", name));
+    }
+    Ok(dir.join(format!("{}.jsonl", name)))
+}
+
+/// 读取录制文件
+pub fn load_recording(dir: &Path, name: &str) -> Result<Vec<RecordedExchange>, String> {
+    let path = recording_path(dir, name)?;
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("读取录制失败: {}", e))?;
+
+    content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| serde_json::from_str(l).map_err(|e| format!("录制文件损坏: {}", e)))
+        .collect()
+}
+
+/// 列出目录下的录制
+pub fn list_recordings(dir: &Path) -> Result<Vec<RecordingInfo>, String> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut recordings = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+
+        let name = match path.file_stem().and_then(|s| s.to_str()) {
+            Some(n) => n.to_string(),
+            None => continue,
+        };
+        let meta = std::fs::metadata(&path).map_err(|e| e.to_string())?;
+        let exchanges = std::fs::read_to_string(&path)
+            .map(|c| c.lines().filter(|l| !l.trim().is_empty()).count())
+            .unwrap_or(0);
+        let modified_at = meta
+            .modified()
+            .ok()
+            .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339());
+
+        recordings.push(RecordingInfo {
+            name,
+            exchanges,
+            size_bytes: meta.len(),
+            modified_at,
+        });
+    }
+
+    recordings.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+    Ok(recordings)
+}
+
+pub fn delete_recording(dir: &Path, name: &str) -> Result<bool, String> {
+    let path = recording_path(dir, name)?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 录制模式下记录请求快照，其他模式返回 None
+pub(crate) fn capture(tags: &RequestTags, request: &reqwest::Request) -> Option<PendingExchange> {
+    if mode() != RecorderMode::Record {
+        return None;
+    }
+
+    let body = request.body().and_then(|b| b.as_bytes());
+    Some(PendingExchange {
+        exchange: RecordedExchange {
+            seq: 0,
+            recorded_at: chrono::Utc::now().to_rfc3339(),
+            run_id: tags.run_id.clone(),
+            wallet: tags.wallet.as_ref().map(|w| w.to_lowercase()),
+            method: request.method().to_string(),
+            url: redact_url(request.url()),
+            rpc_method: body.and_then(rpc_method),
+            request_headers: redact_headers(request.headers()),
+            request_body: body.map(|b| redact_body(request.headers(), b)),
+            status: None,
+            response_headers: Vec::new(),
+            response_body: None,
+            response_body_hex: false,
+            error: None,
+        },
+    })
+}
+
+/// 写入一次请求的结果
+pub(crate) fn finish(pending: PendingExchange, outcome: &Result<RawResponse, TransportFailure>) {
+    let mut exchange = pending.exchange;
+
+    match outcome {
+        Ok(response) => {
+            exchange.status = Some(response.status.as_u16());
+            exchange.response_headers = redact_headers(&response.headers);
+            match &response.body {
+                Ok(body) => match std::str::from_utf8(body) {
+                    Ok(_) => {
+                        exchange.response_body = Some(redact_body(&response.headers, body));
+                    }
+                    Err(_) => {
+                        exchange.response_body = Some(hex::encode(body));
+                        exchange.response_body_hex = true;
+                    }
+                },
+                Err(e) => exchange.error = Some(e.clone()),
+            }
+        }
+        Err(failure) => exchange.error = Some(failure.message.clone()),
+    }
+
+    let mut state = STATE.lock().unwrap();
+    if state.mode != RecorderMode::Record {
+        return;
+    }
+
+    state.seq += 1;
+    exchange.seq = state.seq;
+
+    let name = exchange.run_id.clone().unwrap_or_else(|| ADHOC_RECORDING.to_string());
+    let path = state.dir.join(format!("{}.jsonl", name));
+    let written = serde_json::to_string(&exchange)
+        .map_err(|e| e.to_string())
+        .and_then(|line| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut f| writeln!(f, "{}", line))
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = written {
+        eprintln!("HTTP recording write error: {}", e);
+    }
+}
+
+/// 取出与请求匹配的下一条录制
+pub(crate) fn replay(tags: &RequestTags, request: &reqwest::Request) -> ReplayOutcome {
+    let body = request.body().and_then(|b| b.as_bytes());
+    let probe = RecordedExchange {
+        seq: 0,
+        recorded_at: String::new(),
+        run_id: None,
+        wallet: tags.wallet.as_ref().map(|w| w.to_lowercase()),
+        method: request.method().to_string(),
+        url: redact_url(request.url()),
+        rpc_method: body.and_then(rpc_method),
+        request_headers: Vec::new(),
+        request_body: None,
+        status: None,
+        response_headers: Vec::new(),
+        response_body: None,
+        response_body_hex: false,
+        error: None,
+    };
+    let key = probe.replay_key();
+
+    let recorded = {
+        let mut state = STATE.lock().unwrap();
+        match state.queues.get_mut(&key).and_then(|q| q.pop_front()) {
+            Some(r) => {
+                state.served += 1;
+                r
+            }
+            None => {
+                state.missed.push(key.clone());
+                return ReplayOutcome::Missing(format!("No recorded response for {}", key));
+            }
+        }
+    };
+
+    let status = match recorded.status.and_then(|s| StatusCode::from_u16(s).ok()) {
+        Some(s) => s,
+        None => {
+            return ReplayOutcome::Failure(recorded.error.unwrap_or_else(|| "recorded failure".into()))
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &recorded.response_headers {
+        if let (Ok(n), Ok(v)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            headers.append(n, v);
+        }
+    }
+
+    let body = match (recorded.response_body, recorded.error) {
+        (_, Some(e)) => Err(e),
+        (Some(b), None) if recorded.response_body_hex => hex::decode(b).map_err(|e| e.to_string()),
+        (Some(b), None) => Ok(align_rpc_ids(body, b.into_bytes())),
+        (None, None) => Ok(Vec::new()),
+    };
+
+    ReplayOutcome::Response(RawResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+fn is_sensitive(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SENSITIVE_KEYS.iter().any(|k| name.contains(k))
+}
+
+fn redact_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_sensitive(name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+fn redact_url(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    // 代理凭据不会出现在请求URL中，这里只处理查询参数
+    if url.query().is_some() {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| {
+                let v = if is_sensitive(&k) { REDACTED.to_string() } else { v.into_owned() };
+                (k.into_owned(), v)
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url.to_string()
+}
+
+/// 把 JSON 中敏感字段的值替换为占位符
+pub(crate) fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if is_sensitive(key) && !v.is_object() && !v.is_array() {
+                    *v = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// 脱敏请求/响应体：JSON 按字段名，表单按参数名，其他原样保存
+fn redact_body(headers: &HeaderMap, body: &[u8]) -> String {
+    if let Ok(mut json) = serde_json::from_slice::<Value>(body) {
+        redact_json(&mut json);
+        return json.to_string();
+    }
+
+    let is_form = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/x-www-form-urlencoded"));
+    if is_form {
+        return String::from_utf8_lossy(body)
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((k, _)) if is_sensitive(k) => format!("{}={}", k, REDACTED),
+                _ => pair.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+    }
+
+    String::from_utf8_lossy(body).into_owned()
+}
+
+/// JSON-RPC 请求的方法名
+fn rpc_method(body: &[u8]) -> Option<String> {
+    let json: Value = serde_json::from_slice(body).ok()?;
+    let method = |v: &Value| v.get("method").and_then(|m| m.as_str()).map(|m| m.to_string());
+
+    match &json {
+        Value::Object(_) => method(&json),
+        Value::Array(items) => {
+            let methods: Vec<String> = items.iter().filter_map(method).collect();
+            (!methods.is_empty()).then(|| methods.join(","))
+        }
+        _ => None,
+    }
+}
+
+/// 把回放响应中的 JSON-RPC id 改为本次请求的 id（id 由客户端递增生成，每次运行不同）
+fn align_rpc_ids(request_body: Option<&[u8]>, response: Vec<u8>) -> Vec<u8> {
+    let request: Value = match request_body.and_then(|b| serde_json::from_slice(b).ok()) {
+        Some(v) => v,
+        None => return response,
+    };
+    let mut parsed: Value = match serde_json::from_slice(&response) {
+        Ok(v) => v,
+        Err(_) => return response,
+    };
+
+    match (&request, &mut parsed) {
+        (Value::Object(req), Value::Object(resp)) => {
+            if let (Some(id), true) = (req.get("id"), resp.contains_key("id")) {
+                resp.insert("id".into(), id.clone());
+            }
+        }
+        // 批量请求按顺序对应
+        (Value::Array(reqs), Value::Array(resps)) => {
+            for (req, resp) in reqs.iter().zip(resps.iter_mut()) {
+                if let (Some(id), Some(obj)) = (req.get("id"), resp.as_object_mut()) {
+                    obj.insert("id".into(), id.clone());
+                }
+            }
+        }
+        _ => return response,
+    }
+
+    serde_json::to_vec(&parsed).unwrap_or(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::http::{create_client, HttpClient, HttpError};
+    use crate::common::rpc::create_rpc_client;
+    use crate::modules::scripts::pharos::api::PharosClient;
+    use alloy::primitives::{Address, U256};
+    use alloy::providers::{Provider, ProviderBuilder};
+    use serde_json::json;
+
+    const FIXTURE_WALLET: &str = "0x1111111111111111111111111111111111111111";
+    const FIXTURE_RPC_URL: &str = "https://atlantic.dplabs-internal.com";
+
+    fn json_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers
+    }
+
+    #[tokio::test]
+    async fn replays_pharos_fixture_offline() {
+        let _guard = TEST_LOCK.lock().await;
+
+        let dir = std::env::temp_dir().join(format!("recorder-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("pharos_run.jsonl"),
+            include_str!("fixtures/pharos_run.jsonl"),
+        )
+        .unwrap();
+        assert_eq!(start_replay(dir.clone(), "pharos_run").unwrap(), 6);
+
+        let http = HttpClient::new(
+            create_client(None).unwrap(),
+            RequestTags {
+                proxy_id: None,
+                wallet: Some(FIXTURE_WALLET.to_uppercase().replacen("0X", "0x", 1)),
+                run_id: Some("replay-run".to_string()),
+            },
+        );
+
+        // 签名、邀请码等查询参数不参与匹配
+        let login = PharosClient::new(FIXTURE_WALLET, None, http.clone())
+            .login("0xdeadbeef", "other-code")
+            .await
+            .unwrap();
+        assert_eq!(login.data.and_then(|d| d.jwt).as_deref(), Some(REDACTED));
+
+        let api = PharosClient::new(FIXTURE_WALLET, Some("jwt".to_string()), http.clone());
+        assert_eq!(api.check_in().await.unwrap().code, 0);
+        assert!(!api.get_faucet_status().await.unwrap().data.unwrap().is_able_to_faucet);
+        match api.claim_faucet().await {
+            Err(HttpError::Api { code, message }) => {
+                assert_eq!(code, 1);
+                assert_eq!(message, "faucet did not cooldown");
+            }
+            other => panic!("unexpected faucet result: {:?}", other),
+        }
+
+        // RPC 响应的 id 按本次请求改写，alloy 才能对上
+        let provider = ProviderBuilder::new().connect_client(create_rpc_client(&http, FIXTURE_RPC_URL));
+        let balance = provider
+            .get_balance(FIXTURE_WALLET.parse::<Address>().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(balance, U256::from(1_000_000_000_000_000_000u128));
+
+        let info = api.get_profile().await.unwrap().data.unwrap().user_info.unwrap();
+        assert_eq!((info.id, info.task_points, info.total_points), (1024, 40, 250));
+
+        // 录制用完后再请求会报告缺失，而不是联网
+        assert!(matches!(api.check_in().await, Err(HttpError::Replay(_))));
+
+        let final_status = stop();
+        assert_eq!(final_status.served, 6);
+        assert_eq!(final_status.unused, 0);
+        assert_eq!(final_status.missed.len(), 1);
+        assert_eq!(mode(), RecorderMode::Off);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn redact_url_masks_sensitive_query_params() {
+        let url = reqwest::Url::parse(
+            "https://api.example.com/user/login?address=0xabc&signature=0xsig&invite_code=XYZ&Token=t",
+        )
+        .unwrap();
+        assert_eq!(
+            redact_url(&url),
+            "https://api.example.com/user/login?address=0xabc&signature=%5BREDACTED%5D&invite_code=XYZ&Token=%5BREDACTED%5D"
+        );
+
+        let plain = reqwest::Url::parse("https://api.example.com/faucet/status").unwrap();
+        assert_eq!(redact_url(&plain), "https://api.example.com/faucet/status");
+    }
+
+    #[test]
+    fn redact_body_masks_nested_json_fields() {
+        let body = json!({
+            "clientKey": "key-123",
+            "task": {"type": "RecaptchaV3TaskProxyless", "websiteKey": "site"},
+            "data": {"jwt": "eyJ...", "items": [{"password": "p", "name": "n"}]},
+            "auth": {"token": {"value": "nested"}}
+        });
+        let redacted: Value =
+            serde_json::from_str(&redact_body(&json_headers(), body.to_string().as_bytes())).unwrap();
+
+        assert_eq!(redacted["clientKey"], REDACTED);
+        assert_eq!(redacted["task"]["websiteKey"], "site");
+        assert_eq!(redacted["data"]["jwt"], REDACTED);
+        assert_eq!(redacted["data"]["items"][0]["password"], REDACTED);
+        assert_eq!(redacted["data"]["items"][0]["name"], "n");
+        // 敏感字段是对象时递归处理内部字段
+        assert_eq!(redacted["auth"]["token"]["value"], "nested");
+    }
+
+    #[test]
+    fn redact_body_handles_forms_and_plain_text() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded; charset=utf-8"),
+        );
+        assert_eq!(
+            redact_body(&headers, b"address=0xabc&api_key=secret&amount=1"),
+            format!("address=0xabc&api_key={}&amount=1", REDACTED)
+        );
+
+        assert_eq!(redact_body(&HeaderMap::new(), b"token=abc"), "token=abc");
+    }
+
+    #[test]
+    fn align_rpc_ids_uses_request_ids() {
+        let request = json!({"jsonrpc": "2.0", "id": 7, "method": "eth_chainId"}).to_string();
+        let recorded = json!({"jsonrpc": "2.0", "id": 99, "result": "0x1"}).to_string();
+        let aligned: Value = serde_json::from_slice(&align_rpc_ids(
+            Some(request.as_bytes()),
+            recorded.into_bytes(),
+        ))
+        .unwrap();
+        assert_eq!(aligned["id"], 7);
+        assert_eq!(aligned["result"], "0x1");
+
+        let batch = json!([
+            {"jsonrpc": "2.0", "id": 3, "method": "eth_blockNumber"},
+            {"jsonrpc": "2.0", "id": 4, "method": "eth_gasPrice"}
+        ])
+        .to_string();
+        let recorded = json!([
+            {"jsonrpc": "2.0", "id": 10, "result": "0x10"},
+            {"jsonrpc": "2.0", "id": 11, "result": "0x20"}
+        ])
+        .to_string();
+        let aligned: Value =
+            serde_json::from_slice(&align_rpc_ids(Some(batch.as_bytes()), recorded.into_bytes()))
+                .unwrap();
+        assert_eq!(aligned[0]["id"], 3);
+        assert_eq!(aligned[1]["id"], 4);
+        assert_eq!(rpc_method(batch.as_bytes()).as_deref(), Some("eth_blockNumber,eth_gasPrice"));
+    }
+
+    #[test]
+    fn align_rpc_ids_leaves_non_rpc_bodies_alone() {
+        let body = b"not json".to_vec();
+        assert_eq!(align_rpc_ids(Some(b"{\"id\":1}"), body.clone()), body);
+
+        let response = json!({"code": 0}).to_string().into_bytes();
+        assert_eq!(align_rpc_ids(None, response.clone()), response);
+        // 响应本身没有 id 字段时不添加
+        let aligned = align_rpc_ids(Some(b"{\"id\":1}"), response.clone());
+        assert_eq!(serde_json::from_slice::<Value>(&aligned).unwrap(), json!({"code": 0}));
+    }
+}
@@ -0,0 +1,71 @@
+use alloy::rpc::client::RpcClient;
+use alloy::rpc::json_rpc::{RequestPacket, ResponsePacket};
+use alloy::transports::{TransportError, TransportErrorKind, TransportFut};
+use std::task::{Context, Poll};
+use tower::Service;
+
+use super::http::{HttpClient, HttpError};
+
+/// 有副作用、不能自动重试的 RPC 方法
+const SEND_METHODS: &[&str] = &["eth_sendRawTransaction", "eth_sendTransaction"];
+
+/// 走 `HttpClient` 的 JSON-RPC 传输层，RPC 请求与 API 请求共用代理和流量统计
+#[derive(Clone)]
+pub struct RpcTransport {
+    http: HttpClient,
+    url: String,
+}
+
+impl RpcTransport {
+    pub fn new(http: HttpClient, url: &str) -> Self {
+        Self {
+            http,
+            url: url.to_string(),
+        }
+    }
+
+    async fn do_request(self, req: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let request = self
+            .http
+            .post(&self.url)
+            .headers(req.headers())
+            .json(&req);
+
+        // 查询无副作用，按幂等请求重试；广播交易不重试：首次请求可能已到达节点，
+        // 重发会得到 "already known" / "nonce too low"，调用方拿不到交易哈希
+        let broadcasts = req.method_names().any(|m| SEND_METHODS.contains(&m));
+        let response = if broadcasts {
+            self.http.send(request).await
+        } else {
+            self.http.send_idempotent(request).await
+        };
+        let response = response.map_err(|e| match e {
+            HttpError::Status { status, body } => {
+                TransportErrorKind::http_error(status.as_u16(), body)
+            }
+            e => TransportErrorKind::custom_str(&e.to_string()),
+        })?;
+
+        serde_json::from_slice(&response.body)
+            .map_err(|err| TransportError::deser_err(err, response.text()))
+    }
+}
+
+impl Service<RequestPacket> for RpcTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().do_request(req))
+    }
+}
+
+/// 创建走 `HttpClient` 的 RPC 客户端
+pub fn create_rpc_client(http: &HttpClient, url: &str) -> RpcClient {
+    RpcClient::new(RpcTransport::new(http.clone(), url), false)
+}
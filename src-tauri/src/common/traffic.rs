@@ -0,0 +1,155 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use super::http::RequestTags;
+
+lazy_static! {
+    static ref METER: Mutex<TrafficMeter> = Mutex::new(TrafficMeter::default());
+}
+
+/// 统计维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrafficScope {
+    Proxy,
+    Wallet,
+    Run,
+}
+
+/// 某一天某个维度的累计流量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrafficRecord {
+    pub date: String, // YYYY-MM-DD (UTC)
+    pub scope: TrafficScope,
+    pub key: String,
+    pub requests: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+impl TrafficRecord {
+    pub fn total_bytes(&self) -> u64 {
+        self.bytes_sent + self.bytes_received
+    }
+}
+
+#[derive(Default)]
+struct TrafficMeter {
+    /// 尚未落盘的增量
+    pending: HashMap<(String, TrafficScope, String), TrafficRecord>,
+    /// 因超出预算被暂停的代理
+    paused_proxies: HashSet<String>,
+}
+
+/// 记录一次请求的流量
+pub fn record(tags: &RequestTags, bytes_sent: u64, bytes_received: u64) {
+    let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let mut meter = METER.lock().unwrap();
+
+    let keys = [
+        (TrafficScope::Proxy, tags.proxy_id.as_ref()),
+        (TrafficScope::Wallet, tags.wallet.as_ref()),
+        (TrafficScope::Run, tags.run_id.as_ref()),
+    ];
+
+    for (scope, key) in keys {
+        let key = match key {
+            Some(k) => k.to_lowercase(),
+            None => continue,
+        };
+        let entry = meter
+            .pending
+            .entry((date.clone(), scope, key.clone()))
+            .or_insert_with(|| TrafficRecord {
+                date: date.clone(),
+                scope,
+                key,
+                requests: 0,
+                bytes_sent: 0,
+                bytes_received: 0,
+            });
+        entry.requests += 1;
+        entry.bytes_sent += bytes_sent;
+        entry.bytes_received += bytes_received;
+    }
+}
+
+/// 取出所有未落盘的增量
+pub fn take_pending() -> Vec<TrafficRecord> {
+    let mut meter = METER.lock().unwrap();
+    meter.pending.drain().map(|(_, v)| v).collect()
+}
+
+/// 落盘失败时把取出的增量放回，等待下次写入
+pub fn restore_pending(records: Vec<TrafficRecord>) {
+    let mut meter = METER.lock().unwrap();
+    for delta in records {
+        match meter
+            .pending
+            .entry((delta.date.clone(), delta.scope, delta.key.clone()))
+        {
+            std::collections::hash_map::Entry::Occupied(mut e) => {
+                let existing = e.get_mut();
+                existing.requests += delta.requests;
+                existing.bytes_sent += delta.bytes_sent;
+                existing.bytes_received += delta.bytes_received;
+            }
+            std::collections::hash_map::Entry::Vacant(e) => {
+                e.insert(delta);
+            }
+        }
+    }
+}
+
+/// 将增量合并进已有记录
+pub fn merge_records(records: &mut Vec<TrafficRecord>, pending: Vec<TrafficRecord>) {
+    for delta in pending {
+        match records
+            .iter_mut()
+            .find(|r| r.date == delta.date && r.scope == delta.scope && r.key == delta.key)
+        {
+            Some(existing) => {
+                existing.requests += delta.requests;
+                existing.bytes_sent += delta.bytes_sent;
+                existing.bytes_received += delta.bytes_received;
+            }
+            None => records.push(delta),
+        }
+    }
+}
+
+/// 更新被暂停的代理集合
+pub fn set_paused_proxies(ids: HashSet<String>) {
+    METER.lock().unwrap().paused_proxies = ids;
+}
+
+pub fn is_proxy_paused(proxy_id: &str) -> bool {
+    METER.lock().unwrap().paused_proxies.contains(proxy_id)
+}
+
+/// 估算请求大小：请求行 + 请求头 + 请求体
+pub fn request_size(request: &reqwest::Request) -> u64 {
+    let line = request.method().as_str().len() + request.url().as_str().len() + 12;
+    let headers: usize = request
+        .headers()
+        .iter()
+        .map(|(k, v)| k.as_str().len() + v.len() + 4)
+        .sum();
+    let body = request
+        .body()
+        .and_then(|b| b.as_bytes())
+        .map(|b| b.len())
+        .unwrap_or(0);
+    (line + headers + body) as u64
+}
+
+/// 估算响应大小：状态行 + 响应头 + 响应体
+pub fn response_size(headers: &reqwest::header::HeaderMap, body_len: usize) -> u64 {
+    let header_bytes: usize = headers
+        .iter()
+        .map(|(k, v)| k.as_str().len() + v.len() + 4)
+        .sum();
+    (17 + header_bytes + body_len) as u64
+}
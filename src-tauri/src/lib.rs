@@ -14,6 +14,26 @@ pub fn run() {
         .setup(|app| {
             // 创建系统托盘
             tray::create_tray(&app.handle())?;
+            // 网络请求重试与限速设置
+            if let Ok(settings) = system::load_http_settings(app.handle()) {
+                system::apply_http_settings(&settings);
+            }
+            // 代理订阅定时同步
+            proxy::spawn_source_sync(app.handle().clone());
+            // 流量统计定时落盘
+            proxy::spawn_traffic_flush(app.handle().clone());
+            // 代理失败隔离与复检
+            proxy::spawn_health_monitor(app.handle().clone());
+            // 打码记录定时落盘
+            system::spawn_captcha_usage_flush(app.handle().clone());
+            // 钱包 cookie 定时落盘
+            wallet::spawn_cookie_flush(app.handle().clone());
+            // 上次未正常结束的运行标记为中断，可在页面中恢复
+            if let Err(e) = scripts::mark_interrupted_runs(app.handle()) {
+                eprintln!("Failed to check interrupted runs: {}", e);
+            }
+            // 脚本定时运行
+            scripts::spawn_scheduler(app.handle().clone());
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -75,6 +95,14 @@ pub fn run() {
             wallet::get_wallet_private_key,
             wallet::update_wallet_name,
             wallet::delete_wallet,
+            wallet::update_wallet_proxy_fallback,
+            wallet::update_wallet_tags,
+            wallet::get_wallet_fingerprint,
+            wallet::regenerate_wallet_fingerprint,
+            wallet::get_wallet_cookies,
+            wallet::import_wallet_cookies,
+            wallet::export_wallet_cookies,
+            wallet::clear_wallet_cookies,
             
             // ========== 社交账号模块 (Social) ==========
             social::validate_social_token,
@@ -90,13 +118,69 @@ pub fn run() {
             proxy::delete_proxy,
             proxy::update_proxy,
             proxy::ping_proxy,
+            proxy::rotate_proxy_session,
+            proxy::get_proxy_sources,
+            proxy::add_proxy_source,
+            proxy::update_proxy_source,
+            proxy::delete_proxy_source,
+            proxy::sync_proxy_source,
+            proxy::sync_all_proxy_sources,
+            proxy::get_traffic_usage,
+            proxy::release_proxy_quarantine,
+            proxy::check_wallet_exit_ips,
+            proxy::get_exit_ip_history,
+            proxy::check_sybil_links,
+            proxy::check_planned_links,
             
             // ========== 系统模块 (System) ==========
             system::get_system_info,
             system::get_app_settings,
             system::save_app_settings,
+            system::get_http_settings,
+            system::save_http_settings,
+            system::get_effective_rate_limits,
+            system::start_http_recording,
+            system::start_http_replay,
+            system::stop_http_recorder,
+            system::get_http_recorder_status,
+            system::list_http_recordings,
+            system::delete_http_recording,
+            system::get_captcha_settings,
+            system::save_captcha_settings,
+            system::get_captcha_balances,
+            system::get_captcha_usage,
+            system::get_captcha_usage_summary,
             system::copy_to_clipboard,
 
+            // ========== 脚本模块 (Scripts) ==========
+            scripts::list_scripts,
+            scripts::start_script,
+            scripts::stop_script,
+            scripts::script_status,
+            scripts::list_script_presets,
+            scripts::save_script_preset,
+            scripts::delete_script_preset,
+            scripts::get_task_ledger,
+            scripts::clear_task_ledger,
+            scripts::list_script_runs,
+            scripts::get_script_run,
+            scripts::diff_script_runs,
+            scripts::export_script_run_csv,
+            scripts::query_script_logs,
+            scripts::export_script_logs,
+            scripts::subscribe_script_logs,
+            scripts::unsubscribe_script_logs,
+            scripts::list_scheduled_jobs,
+            scripts::save_scheduled_job,
+            scripts::delete_scheduled_job,
+            scripts::set_scheduled_job_enabled,
+            scripts::run_scheduled_job_now,
+            scripts::preview_schedule,
+            scripts::get_work_queue,
+            scripts::list_interrupted_runs,
+            scripts::resume_script_run,
+            scripts::discard_interrupted_run,
+
             // ========== Pharos 模块 ==========
             modules::scripts::pharos::execute_pharos_login,
             modules::scripts::pharos::start_pharos_tasks,
@@ -1,4 +1,12 @@
 use super::types::*;
+use super::utils::*;
+use super::sources::*;
+use super::traffic::*;
+use super::health::*;
+use super::exit_ip::*;
+use crate::common::traffic::{TrafficRecord, TrafficScope};
+use crate::common::http::generate_session_id;
+use std::collections::HashMap;
 use tauri::Manager;
 
 #[tauri::command]
@@ -25,47 +33,38 @@ pub async fn add_proxy(
     username: Option<String>,
     password: Option<String>,
     country: Option<String>,
+    session_mode: Option<ProxySessionMode>,
 ) -> Result<ProxyIP, String> {
-    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
-    let proxies_path = data_dir.join("proxies.json");
-    
-    // 读取现有代理列表
-    let mut proxies: Vec<ProxyIP> = if proxies_path.exists() {
-        let content = std::fs::read_to_string(&proxies_path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
-    } else {
-        Vec::new()
-    };
-    
-    // 检查是否已存在（相同IP和端口）
-    let exists = proxies.iter().any(|p| p.ip == ip && p.port == port);
-    if exists {
-        return Err("该代理IP已经存在".to_string());
-    }
-    
-    // 创建新代理
-    let new_proxy = ProxyIP {
-        id: format!("{}", proxies.len() + 1),
-        ip: ip.clone(),
-        port,
-        protocol,
-        username,
-        password,
-        country,
-        status: "inactive".to_string(),
-        wallet_bindings: Vec::new(),
-        created_at: chrono::Utc::now().to_rfc3339(),
-        last_used: None,
-    };
-    
-    proxies.push(new_proxy.clone());
-    
-    // 保存到文件
-    let json = serde_json::to_string_pretty(&proxies).map_err(|e| e.to_string())?;
-    std::fs::write(&proxies_path, json).map_err(|e| e.to_string())?;
-    
-    Ok(new_proxy)
+    update_proxies(&app, |proxies| {
+        // 检查是否已存在（相同IP和端口）
+        let exists = proxies.iter().any(|p| p.ip == ip && p.port == port);
+        if exists {
+            return Err("该代理IP已经存在".to_string());
+        }
+        
+        // 创建新代理
+        let new_proxy = ProxyIP {
+            id: next_proxy_id(proxies),
+            ip: ip.clone(),
+            port,
+            protocol,
+            username,
+            password,
+            country,
+            status: "inactive".to_string(),
+            wallet_bindings: Vec::new(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            last_used: None,
+            session_mode: session_mode.unwrap_or_default(),
+            sticky_sessions: HashMap::new(),
+            source_id: None,
+            daily_budget_mb: None,
+            ..Default::default()
+        };
+        
+        proxies.push(new_proxy.clone());
+        Ok(new_proxy)
+    })
 }
 
 #[tauri::command]
@@ -73,102 +72,68 @@ pub async fn batch_add_proxies(
     app: tauri::AppHandle,
     proxies_text: String,
 ) -> Result<(usize, usize), String> {
-    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
-    let proxies_path = data_dir.join("proxies.json");
-    
-    // 读取现有代理列表
-    let mut existing_proxies: Vec<ProxyIP> = if proxies_path.exists() {
-        let content = std::fs::read_to_string(&proxies_path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
-    } else {
-        Vec::new()
-    };
-    
-    let mut success_count = 0;
-    let mut fail_count = 0;
-    
-    // 解析每一行（格式：ip:port:protocol 或 ip:port:protocol:username:password）
-    for line in proxies_text.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
+    update_proxies(&app, |existing_proxies| {
+        let mut success_count = 0;
+        let mut fail_count = 0;
+    
+        // 解析每一行（格式：ip:port:protocol 或 ip:port:protocol:username:password）
+        for line in proxies_text.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
         
-        let parts: Vec<&str> = line.split(':').collect();
-        if parts.len() < 3 {
-            fail_count += 1;
-            continue;
-        }
+            let ParsedProxy { ip, port, protocol, username, password } =
+                match parse_proxy_line(line, ProxyListFormat::IpPortProtocol, "http") {
+                    Some(p) => p,
+                    None => {
+                        fail_count += 1;
+                        continue;
+                    }
+                };
         
-        let ip = parts[0].to_string();
-        let port = match parts[1].parse::<u16>() {
-            Ok(p) => p,
-            Err(_) => {
+            // 检查是否已存在
+            let exists = existing_proxies.iter().any(|p| p.ip == ip && p.port == port);
+            if exists {
                 fail_count += 1;
                 continue;
             }
-        };
-        let protocol = parts[2].to_string();
-        let username = if parts.len() > 3 { Some(parts[3].to_string()) } else { None };
-        let password = if parts.len() > 4 { Some(parts[4].to_string()) } else { None };
         
-        // 检查是否已存在
-        let exists = existing_proxies.iter().any(|p| p.ip == ip && p.port == port);
-        if exists {
-            fail_count += 1;
-            continue;
-        }
+            // 添加新代理
+            let new_proxy = ProxyIP {
+                id: next_proxy_id(existing_proxies),
+                ip,
+                port,
+                protocol,
+                username,
+                password,
+                country: None,
+                status: "inactive".to_string(),
+                wallet_bindings: Vec::new(),
+                created_at: chrono::Utc::now().to_rfc3339(),
+                last_used: None,
+                session_mode: ProxySessionMode::default(),
+                sticky_sessions: HashMap::new(),
+                source_id: None,
+                daily_budget_mb: None,
+                ..Default::default()
+            };
         
-        // 添加新代理
-        let new_proxy = ProxyIP {
-            id: format!("{}", existing_proxies.len() + 1),
-            ip,
-            port,
-            protocol,
-            username,
-            password,
-            country: None,
-            status: "inactive".to_string(),
-            wallet_bindings: Vec::new(),
-            created_at: chrono::Utc::now().to_rfc3339(),
-            last_used: None,
-        };
+            existing_proxies.push(new_proxy);
+            success_count += 1;
+        }
         
-        existing_proxies.push(new_proxy);
-        success_count += 1;
-    }
-    
-    // 保存到文件
-    let json = serde_json::to_string_pretty(&existing_proxies).map_err(|e| e.to_string())?;
-    std::fs::write(&proxies_path, json).map_err(|e| e.to_string())?;
-    
-    Ok((success_count, fail_count))
+        Ok((success_count, fail_count))
+    })
 }
 
 #[tauri::command]
 pub async fn delete_proxy(app: tauri::AppHandle, id: String) -> Result<bool, String> {
-    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let proxies_path = data_dir.join("proxies.json");
-    
-    if !proxies_path.exists() {
-        return Ok(false);
-    }
-    
-    let content = std::fs::read_to_string(&proxies_path).map_err(|e| e.to_string())?;
-    let mut proxies: Vec<ProxyIP> = serde_json::from_str(&content).unwrap_or_else(|_| Vec::new());
-    
-    let original_len = proxies.len();
-    proxies.retain(|p| p.id != id);
-    
-    if proxies.len() == original_len {
-        return Ok(false);
-    }
-    
-    let json = serde_json::to_string_pretty(&proxies).map_err(|e| e.to_string())?;
-    std::fs::write(&proxies_path, json).map_err(|e| e.to_string())?;
-    
-    Ok(true)
+    update_proxies(&app, |proxies| {
+        let original_len = proxies.len();
+        proxies.retain(|p| p.id != id);
+        Ok(proxies.len() != original_len)
+    })
 }
 
 #[tauri::command]
@@ -182,54 +147,47 @@ pub async fn update_proxy(
     password: Option<String>,
     country: Option<String>,
     wallet_bindings: Option<Vec<String>>,
+    session_mode: Option<ProxySessionMode>,
+    daily_budget_mb: Option<f64>,
 ) -> Result<bool, String> {
-    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let proxies_path = data_dir.join("proxies.json");
-    
-    if !proxies_path.exists() {
-        return Ok(false);
-    }
-    
-    let content = std::fs::read_to_string(&proxies_path).map_err(|e| e.to_string())?;
-    let mut proxies: Vec<ProxyIP> = serde_json::from_str(&content).unwrap_or_else(|_| Vec::new());
-    
-    let mut found = false;
-    for proxy in &mut proxies {
-        if proxy.id == id {
-            if let Some(new_ip) = ip {
-                proxy.ip = new_ip;
-            }
-            if let Some(new_port) = port {
-                proxy.port = new_port;
-            }
-            if let Some(new_protocol) = protocol {
-                proxy.protocol = new_protocol;
-            }
-            if let Some(new_username) = username {
-                proxy.username = Some(new_username);
+    update_proxies(&app, |proxies| {
+        let mut found = false;
+        for proxy in proxies.iter_mut() {
+            if proxy.id == id {
+                if let Some(new_ip) = ip {
+                    proxy.ip = new_ip;
+                }
+                if let Some(new_port) = port {
+                    proxy.port = new_port;
+                }
+                if let Some(new_protocol) = protocol {
+                    proxy.protocol = new_protocol;
+                }
+                if let Some(new_username) = username {
+                    proxy.username = Some(new_username);
+                }
+                if let Some(new_password) = password {
+                    proxy.password = Some(new_password);
+                }
+                if let Some(new_country) = country {
+                    proxy.country = Some(new_country);
+                }
+                if let Some(new_bindings) = wallet_bindings {
+                    proxy.wallet_bindings = new_bindings;
+                }
+                if let Some(new_mode) = session_mode {
+                    proxy.session_mode = new_mode;
+                }
+                if let Some(new_budget) = daily_budget_mb {
+                    // 0 或负数表示取消预算
+                    proxy.daily_budget_mb = if new_budget > 0.0 { Some(new_budget) } else { None };
+                }
+                found = true;
+                break;
             }
-            if let Some(new_password) = password {
-                proxy.password = Some(new_password);
-            }
-            if let Some(new_country) = country {
-                proxy.country = Some(new_country);
-            }
-            if let Some(new_bindings) = wallet_bindings {
-                proxy.wallet_bindings = new_bindings;
-            }
-            found = true;
-            break;
         }
-    }
-    
-    if !found {
-        return Ok(false);
-    }
-    
-    let json = serde_json::to_string_pretty(&proxies).map_err(|e| e.to_string())?;
-    std::fs::write(&proxies_path, json).map_err(|e| e.to_string())?;
-    
-    Ok(true)
+        Ok(found)
+    })
 }
 
 #[tauri::command]
@@ -240,22 +198,11 @@ pub async fn ping_proxy(
     use std::time::{Duration, Instant};
     use std::net::TcpStream;
     
-    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let proxies_path = data_dir.join("proxies.json");
-    
-    if !proxies_path.exists() {
-        return Err("代理列表不存在".to_string());
-    }
-    
-    let content = std::fs::read_to_string(&proxies_path).map_err(|e| e.to_string())?;
-    let mut proxies: Vec<ProxyIP> = serde_json::from_str(&content).unwrap_or_else(|_| Vec::new());
-    
-    let proxy = proxies.iter_mut().find(|p| p.id == id);
-    if proxy.is_none() {
-        return Err("找不到指定的代理".to_string());
-    }
+    let proxy = load_proxies(&app)?
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| "找不到指定的代理".to_string())?;
     
-    let proxy = proxy.unwrap();
     let addr = format!("{}:{}", proxy.ip, proxy.port);
     
     // 尝试连接
@@ -268,13 +215,275 @@ pub async fn ping_proxy(
     
     let success = result.is_ok();
     
-    // 更新状态
-    proxy.status = if success { "active".to_string() } else { "inactive".to_string() };
-    proxy.last_used = Some(chrono::Utc::now().to_rfc3339());
-    
-    // 保存更新
-    let json = serde_json::to_string_pretty(&proxies).map_err(|e| e.to_string())?;
-    std::fs::write(&proxies_path, json).map_err(|e| e.to_string())?;
+    // 连接结束后在写锁内更新状态，避免覆盖检测期间的其它修改
+    update_proxies(&app, |proxies| {
+        if let Some(proxy) = proxies.iter_mut().find(|p| p.id == id) {
+            proxy.status = if success { "active".to_string() } else { "inactive".to_string() };
+            proxy.last_used = Some(chrono::Utc::now().to_rfc3339());
+        }
+        Ok(())
+    })?;
     
     Ok((success, if success { Some(elapsed) } else { None }))
-}
\ No newline at end of file
+}
+
+/// 强制轮换钱包的粘性会话，返回新的会话ID
+#[tauri::command]
+pub async fn rotate_proxy_session(
+    app: tauri::AppHandle,
+    address: String,
+) -> Result<String, String> {
+    update_proxies(&app, |proxies| {
+        let proxy = proxies
+            .iter_mut()
+            .find(|p| p.wallet_bindings.iter().any(|w| w.eq_ignore_ascii_case(&address)))
+            .ok_or_else(|| "该钱包未绑定代理".to_string())?;
+        
+        if proxy.session_mode != ProxySessionMode::Sticky {
+            return Err("该代理不是粘性会话模式".to_string());
+        }
+        
+        let session_id = generate_session_id();
+        proxy
+            .sticky_sessions
+            .insert(address.to_lowercase(), session_id.clone());
+        
+        Ok(session_id)
+    })
+}
+
+#[tauri::command]
+pub async fn get_proxy_sources(app: tauri::AppHandle) -> Result<Vec<ProxySource>, String> {
+    load_sources(&app)
+}
+
+#[tauri::command]
+pub async fn add_proxy_source(
+    app: tauri::AppHandle,
+    name: String,
+    kind: ProxySourceKind,
+    location: String,
+    format: ProxyListFormat,
+    default_protocol: Option<String>,
+    refresh_interval_secs: Option<u64>,
+) -> Result<ProxySource, String> {
+    update_sources(&app, |sources| {
+        if sources.iter().any(|s| s.name == name) {
+            return Err("该订阅名称已经存在".to_string());
+        }
+    
+        let source = ProxySource {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            kind,
+            location,
+            format,
+            default_protocol: default_protocol.unwrap_or_else(|| "http".to_string()),
+            refresh_interval_secs: refresh_interval_secs.unwrap_or(3600),
+            enabled: true,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            last_synced_at: None,
+            last_error: None,
+        };
+    
+        sources.push(source.clone());
+        Ok(source)
+    })
+}
+
+#[tauri::command]
+pub async fn update_proxy_source(
+    app: tauri::AppHandle,
+    id: String,
+    name: Option<String>,
+    location: Option<String>,
+    format: Option<ProxyListFormat>,
+    default_protocol: Option<String>,
+    refresh_interval_secs: Option<u64>,
+    enabled: Option<bool>,
+) -> Result<bool, String> {
+    update_sources(&app, |sources| {
+        let source = match sources.iter_mut().find(|s| s.id == id) {
+            Some(s) => s,
+            None => return Ok(false),
+        };
+    
+        if let Some(new_name) = name {
+            source.name = new_name;
+        }
+        if let Some(new_location) = location {
+            source.location = new_location;
+        }
+        if let Some(new_format) = format {
+            source.format = new_format;
+        }
+        if let Some(new_protocol) = default_protocol {
+            source.default_protocol = new_protocol;
+        }
+        if let Some(new_interval) = refresh_interval_secs {
+            source.refresh_interval_secs = new_interval;
+        }
+        if let Some(new_enabled) = enabled {
+            source.enabled = new_enabled;
+        }
+    
+        Ok(true)
+    })
+}
+
+/// 删除订阅源，其下代理标记为下线（保留钱包绑定）
+#[tauri::command]
+pub async fn delete_proxy_source(app: tauri::AppHandle, id: String) -> Result<bool, String> {
+    update_sources(&app, |sources| {
+        let original_len = sources.len();
+        sources.retain(|s| s.id != id);
+    
+        if sources.len() == original_len {
+            return Ok(false);
+        }
+    
+        update_proxies(&app, |proxies| {
+            for proxy in proxies.iter_mut().filter(|p| p.source_id.as_deref() == Some(id.as_str())) {
+                proxy.status = "retired".to_string();
+                proxy.source_id = None;
+            }
+            Ok(())
+        })?;
+    
+        Ok(true)
+    })
+}
+
+#[tauri::command]
+pub async fn sync_proxy_source(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<ProxySyncReport, String> {
+    sync_source(&app, &id).await
+}
+
+/// 同步所有启用的订阅源，失败的订阅返回带 error 的报告（同时记录在 last_error 中）
+#[tauri::command]
+pub async fn sync_all_proxy_sources(app: tauri::AppHandle) -> Result<Vec<ProxySyncReport>, String> {
+    let sources = load_sources(&app)?;
+    let mut reports = Vec::new();
+    
+    for source in sources.iter().filter(|s| s.enabled) {
+        match sync_source(&app, &source.id).await {
+            Ok(report) => reports.push(report),
+            Err(e) => reports.push(ProxySyncReport {
+                source_id: source.id.clone(),
+                source_name: source.name.clone(),
+                added: Vec::new(),
+                retired: Vec::new(),
+                restored: Vec::new(),
+                unchanged: 0,
+                invalid_lines: 0,
+                orphaned_bindings: Vec::new(),
+                synced_at: chrono::Utc::now().to_rfc3339(),
+                error: Some(e),
+            }),
+        }
+    }
+    
+    Ok(reports)
+}
+
+/// 查询流量统计（日期格式 YYYY-MM-DD，含首尾）
+#[tauri::command]
+pub async fn get_traffic_usage(
+    app: tauri::AppHandle,
+    scope: Option<TrafficScope>,
+    key: Option<String>,
+    from_date: Option<String>,
+    to_date: Option<String>,
+) -> Result<Vec<TrafficRecord>, String> {
+    // 先落盘，保证包含最新数据
+    flush_traffic(&app)?;
+    
+    let mut records: Vec<TrafficRecord> = load_traffic(&app)?
+        .into_iter()
+        .filter(|r| scope.map_or(true, |s| r.scope == s))
+        .filter(|r| key.as_ref().map_or(true, |k| r.key.eq_ignore_ascii_case(k)))
+        .filter(|r| from_date.as_ref().map_or(true, |d| r.date >= *d))
+        .filter(|r| to_date.as_ref().map_or(true, |d| r.date <= *d))
+        .collect();
+    
+    records.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.key.cmp(&b.key)));
+    
+    Ok(records)
+}
+
+/// 手动解除代理隔离
+#[tauri::command]
+pub async fn release_proxy_quarantine(app: tauri::AppHandle, id: String) -> Result<bool, String> {
+    release_quarantine(&app, &id)
+}
+
+/// 通过各钱包的实际请求链路检测出口IP并记录
+#[tauri::command]
+pub async fn check_wallet_exit_ips(
+    app: tauri::AppHandle,
+    addresses: Option<Vec<String>>,
+) -> Result<Vec<ExitIpObservation>, String> {
+    let addresses = match addresses {
+        Some(a) => a,
+        None => crate::modules::wallet::load_wallets(&app)?
+            .into_iter()
+            .map(|w| w.address)
+            .collect(),
+    };
+    
+    let mut observations = Vec::new();
+    
+    for address in addresses {
+        let http = create_wallet_client(&app, &address, None, None)?;
+        let ip = match detect_exit_ip(&http).await {
+            Ok(ip) => ip,
+            Err(e) => {
+                eprintln!("Exit IP check failed for {}: {}", address, e);
+                continue;
+            }
+        };
+        
+        let observation =
+            record_exit_ip(&app, &http, &address, &ip, ExitIpSource::HealthCheck)
+                .await?;
+        observations.push(observation);
+    }
+    
+    Ok(observations)
+}
+
+#[tauri::command]
+pub async fn get_exit_ip_history(
+    app: tauri::AppHandle,
+    wallet: Option<String>,
+) -> Result<Vec<ExitIpObservation>, String> {
+    Ok(load_exit_ip_history(&app)?
+        .into_iter()
+        .filter(|o| wallet.as_ref().map_or(true, |w| o.wallet.eq_ignore_ascii_case(w)))
+        .collect())
+}
+
+/// 按出口IP / 网段 / ASN 汇总共享出口的钱包
+#[tauri::command]
+pub async fn check_sybil_links(app: tauri::AppHandle) -> Result<SybilLinkReport, String> {
+    let history = load_exit_ip_history(&app)?;
+    Ok(build_link_report(&history))
+}
+
+/// 检查计划的 钱包→代理ID 映射会不会产生新的关联
+#[tauri::command]
+pub async fn check_planned_links(
+    app: tauri::AppHandle,
+    mapping: HashMap<String, String>,
+) -> Result<Vec<PlannedLinkWarning>, String> {
+    let history = load_exit_ip_history(&app)?;
+    let proxies = load_proxies(&app)?;
+    
+    let mut planned: Vec<(String, String)> = mapping.into_iter().collect();
+    planned.sort();
+    
+    Ok(check_planned_mapping(&history, &proxies, &planned))
+}
@@ -0,0 +1,316 @@
+use super::types::*;
+use crate::common::http::HttpClient;
+use lazy_static::lazy_static;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+type LinkIndex = HashMap<(LinkKind, String), BTreeSet<String>>;
+
+lazy_static! {
+    /// 出口IP历史内存副本，首次使用时从磁盘加载，每次记录后在锁内写回
+    static ref EXIT_IP_HISTORY: Mutex<Option<Vec<ExitIpObservation>>> = Mutex::new(None);
+    /// IP -> ASN 查询结果（含查询失败），进程内每个IP只查询一次
+    static ref ASN_CACHE: Mutex<HashMap<String, Option<String>>> = Mutex::new(HashMap::new());
+}
+
+fn load_exit_ip_file(app: &tauri::AppHandle) -> Result<Vec<ExitIpObservation>, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let history_path = data_dir.join("exit_ip_history.json");
+
+    if !history_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&history_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_else(|_| Vec::new()))
+}
+
+fn save_exit_ip_history(
+    app: &tauri::AppHandle,
+    history: &[ExitIpObservation],
+) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    let history_path = data_dir.join("exit_ip_history.json");
+
+    let json = serde_json::to_string_pretty(history).map_err(|e| e.to_string())?;
+    std::fs::write(&history_path, json).map_err(|e| e.to_string())
+}
+
+fn with_history<R>(
+    app: &tauri::AppHandle,
+    f: impl FnOnce(&mut Vec<ExitIpObservation>) -> Result<R, String>,
+) -> Result<R, String> {
+    let mut history = EXIT_IP_HISTORY.lock().unwrap();
+    if history.is_none() {
+        *history = Some(load_exit_ip_file(app)?);
+    }
+    f(history.as_mut().unwrap())
+}
+
+/// 读取出口IP历史
+pub fn load_exit_ip_history(app: &tauri::AppHandle) -> Result<Vec<ExitIpObservation>, String> {
+    with_history(app, |history| Ok(history.clone()))
+}
+
+/// 通过钱包的 HTTP 客户端获取实际出口IP
+pub async fn detect_exit_ip(http: &HttpClient) -> Result<String, String> {
+    let request = http
+        .get("https://api.ipify.org")
+        .timeout(Duration::from_secs(10));
+    let response = http
+        .send(request)
+        .await
+        .map_err(|e| format!("出口IP检测失败: {}", e))?;
+
+    Ok(response.text().trim().to_string())
+}
+
+/// 查询IP所属 ASN
+///
+/// 通过钱包自己的客户端（同一代理链路）查询，查询服务看到的只是该出口IP本身，
+/// 不会把本机真实IP和各代理出口关联起来
+pub async fn lookup_asn(http: &HttpClient, ip: &str) -> Option<String> {
+    if let Some(asn) = ASN_CACHE.lock().unwrap().get(ip) {
+        return asn.clone();
+    }
+
+    let url = format!("https://ipinfo.io/{}/json", ip);
+    let request = http.get(&url).timeout(Duration::from_secs(10));
+    let json: serde_json::Value = http.send(request).await.ok()?.json().ok()?;
+
+    // org 形如 "AS15169 Google LLC"，保留地址、限流等情况下没有该字段
+    let asn = json
+        .get("org")
+        .and_then(|a| a.as_str())
+        .filter(|a| a.starts_with("AS"))
+        .map(|a| a.to_string());
+    ASN_CACHE.lock().unwrap().insert(ip.to_string(), asn.clone());
+    asn
+}
+
+/// 钱包当前的代理和会话与上次观测时相同（出口IP不需要重新检测）
+///
+/// 轮换会话每次都不同，始终视为已变化
+pub fn exit_ip_unchanged(
+    app: &tauri::AppHandle,
+    http: &HttpClient,
+    wallet: &str,
+) -> Result<bool, String> {
+    let proxy = http.proxy();
+    if proxy.session.rotate {
+        return Ok(false);
+    }
+    let proxy_id = http.tags().proxy_id;
+    let wallet = wallet.to_lowercase();
+
+    with_history(app, |history| {
+        Ok(history
+            .iter()
+            .filter(|o| o.wallet == wallet)
+            .max_by(|a, b| a.last_seen.cmp(&b.last_seen))
+            .is_some_and(|last| {
+                last.proxy_id == proxy_id && last.session_id == proxy.session.session_id
+            }))
+    })
+}
+
+/// 记录一次出口IP观测（代理取自客户端当前使用的代理）
+pub async fn record_exit_ip(
+    app: &tauri::AppHandle,
+    http: &HttpClient,
+    wallet: &str,
+    ip: &str,
+    source: ExitIpSource,
+) -> Result<ExitIpObservation, String> {
+    // 同一IP的 ASN 只查询一次
+    let known_asn = with_history(app, |history| {
+        Ok(history
+            .iter()
+            .find(|o| o.ip == ip && o.asn.is_some())
+            .and_then(|o| o.asn.clone()))
+    })?;
+    let asn = match known_asn {
+        Some(a) => Some(a),
+        None => lookup_asn(http, ip).await,
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let observation = ExitIpObservation {
+        wallet: wallet.to_lowercase(),
+        proxy_id: http.tags().proxy_id,
+        session_id: http.proxy().session.session_id,
+        ip: ip.to_string(),
+        asn,
+        source,
+        first_seen: now.clone(),
+        last_seen: now,
+        times_seen: 1,
+    };
+
+    with_history(app, |history| {
+        let observation = upsert_observation(history, observation);
+        save_exit_ip_history(app, history)?;
+        Ok(observation)
+    })
+}
+
+/// 合并一次观测：同一钱包+代理+IP 只累加次数
+fn upsert_observation(
+    history: &mut Vec<ExitIpObservation>,
+    observation: ExitIpObservation,
+) -> ExitIpObservation {
+    match history.iter_mut().find(|o| {
+        o.wallet == observation.wallet && o.ip == observation.ip && o.proxy_id == observation.proxy_id
+    }) {
+        Some(existing) => {
+            existing.last_seen = observation.last_seen;
+            existing.times_seen += 1;
+            existing.source = observation.source;
+            existing.session_id = observation.session_id;
+            if existing.asn.is_none() {
+                existing.asn = observation.asn;
+            }
+            existing.clone()
+        }
+        None => {
+            history.push(observation.clone());
+            observation
+        }
+    }
+}
+
+/// IP 所在网段（IPv4 /24，IPv6 /48）
+pub fn subnet_of(ip: &str) -> Option<String> {
+    match ip.parse::<std::net::IpAddr>().ok()? {
+        std::net::IpAddr::V4(v4) => {
+            let o = v4.octets();
+            Some(format!("{}.{}.{}.0/24", o[0], o[1], o[2]))
+        }
+        std::net::IpAddr::V6(v6) => {
+            let s = v6.segments();
+            Some(format!("{:x}:{:x}:{:x}::/48", s[0], s[1], s[2]))
+        }
+    }
+}
+
+/// 取 ASN 编号部分（"AS15169 Google LLC" -> "AS15169"）
+fn asn_number(asn: &str) -> Option<String> {
+    asn.split_whitespace().next().map(|a| a.to_uppercase())
+}
+
+/// 一次观测对应的关联键
+fn link_keys(ip: &str, asn: Option<&str>) -> Vec<(LinkKind, String)> {
+    let mut keys = vec![(LinkKind::Ip, ip.to_string())];
+    if let Some(subnet) = subnet_of(ip) {
+        keys.push((LinkKind::Subnet, subnet));
+    }
+    if let Some(asn) = asn.and_then(asn_number) {
+        keys.push((LinkKind::Asn, asn));
+    }
+    keys
+}
+
+fn build_index(history: &[ExitIpObservation]) -> LinkIndex {
+    let mut index: LinkIndex = HashMap::new();
+    for o in history {
+        for key in link_keys(&o.ip, o.asn.as_deref()) {
+            index.entry(key).or_default().insert(o.wallet.clone());
+        }
+    }
+    index
+}
+
+/// 根据历史记录生成钱包关联报告
+pub fn build_link_report(history: &[ExitIpObservation]) -> SybilLinkReport {
+    let wallets: BTreeSet<&str> = history.iter().map(|o| o.wallet.as_str()).collect();
+
+    let mut links: Vec<WalletLink> = build_index(history)
+        .into_iter()
+        .filter(|(_, wallets)| wallets.len() > 1)
+        .map(|((kind, value), wallets)| WalletLink {
+            kind,
+            value,
+            wallets: wallets.into_iter().collect(),
+        })
+        .collect();
+
+    links.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then_with(|| b.wallets.len().cmp(&a.wallets.len()))
+            .then_with(|| a.value.cmp(&b.value))
+    });
+
+    SybilLinkReport {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        wallets_checked: wallets.len(),
+        links,
+    }
+}
+
+/// 预测代理的出口：固定凭据代理取最近一次观测到的IP；会话型代理无法预测
+fn predicted_keys(
+    history: &[ExitIpObservation],
+    proxy: &ProxyIP,
+) -> Vec<(LinkKind, String)> {
+    if proxy.session_mode != ProxySessionMode::Static {
+        return Vec::new();
+    }
+
+    match history
+        .iter()
+        .filter(|o| o.proxy_id.as_deref() == Some(proxy.id.as_str()))
+        .max_by(|a, b| a.last_seen.cmp(&b.last_seen))
+    {
+        Some(latest) => link_keys(&latest.ip, latest.asn.as_deref()),
+        // 尚未观测过的固定代理，同一代理被多个钱包使用也是同一出口
+        None => vec![(LinkKind::Ip, format!("proxy:{}", proxy.id))],
+    }
+}
+
+/// 检查计划中的 钱包→代理 映射是否会产生历史中不存在的新关联
+pub fn check_planned_mapping(
+    history: &[ExitIpObservation],
+    proxies: &[ProxyIP],
+    mapping: &[(String, String)],
+) -> Vec<PlannedLinkWarning> {
+    let index = build_index(history);
+    let mut planned: LinkIndex = HashMap::new();
+    let mut warnings = Vec::new();
+
+    for (wallet, proxy_id) in mapping {
+        let wallet = wallet.to_lowercase();
+        let proxy = match proxies.iter().find(|p| &p.id == proxy_id) {
+            Some(p) => p,
+            None => continue,
+        };
+
+        for key in predicted_keys(history, proxy) {
+            let historical = index.get(&key);
+            let already_linked = historical.is_some_and(|w| w.contains(&wallet));
+
+            let mut others: BTreeSet<String> = historical.cloned().unwrap_or_default();
+            if let Some(p) = planned.get(&key) {
+                others.extend(p.iter().cloned());
+            }
+            others.remove(&wallet);
+
+            if !already_linked && !others.is_empty() {
+                warnings.push(PlannedLinkWarning {
+                    wallet: wallet.clone(),
+                    proxy_id: proxy_id.clone(),
+                    kind: key.0,
+                    value: key.1.clone(),
+                    linked_wallets: others.into_iter().collect(),
+                });
+            }
+
+            planned.entry(key).or_default().insert(wallet.clone());
+        }
+    }
+
+    warnings
+}
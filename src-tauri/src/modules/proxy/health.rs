@@ -0,0 +1,196 @@
+use super::types::*;
+use super::utils::*;
+use crate::common::proxy_health::{self, QUARANTINE_THRESHOLD};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tauri::Emitter;
+
+/// 健康事件落盘 / 隔离复检的间隔
+const HEALTH_CHECK_INTERVAL_SECS: u64 = 30;
+/// 首次隔离时长，之后每次复检失败翻倍
+const QUARANTINE_BASE_SECS: i64 = 60;
+const QUARANTINE_MAX_SECS: i64 = 6 * 3600;
+
+/// 第 level 次隔离的退避时长
+pub fn quarantine_backoff_secs(level: u32) -> i64 {
+    let exp = level.saturating_sub(1).min(20);
+    (QUARANTINE_BASE_SECS << exp).min(QUARANTINE_MAX_SECS)
+}
+
+/// 手动下线或预算暂停的代理由各自流程管理状态，健康检查不改动
+fn is_parked(proxy: &ProxyIP) -> bool {
+    matches!(proxy.status.as_str(), "paused" | "retired")
+}
+
+/// 隔离代理，返回是否实际进入隔离
+fn quarantine(proxy: &mut ProxyIP, now: chrono::DateTime<chrono::Utc>) -> bool {
+    if is_parked(proxy) {
+        return false;
+    }
+    proxy.quarantine_level += 1;
+    let backoff = quarantine_backoff_secs(proxy.quarantine_level);
+    proxy.quarantined_until = Some((now + chrono::Duration::seconds(backoff)).to_rfc3339());
+    proxy.status = "quarantined".to_string();
+    true
+}
+
+/// 解除隔离，暂停/下线的代理只清除隔离记录，保留原状态
+///
+/// 恢复可用时收回分配给其绑定钱包的备用代理，钱包回到自己的代理
+fn release(proxies: &mut [ProxyIP], idx: usize) {
+    let proxy = &mut proxies[idx];
+    let restored = !is_parked(proxy);
+    if restored {
+        proxy.status = "active".to_string();
+    }
+    proxy.consecutive_failures = 0;
+    proxy.quarantine_level = 0;
+    proxy.quarantined_until = None;
+
+    if restored {
+        let wallets = proxy.wallet_bindings.clone();
+        release_fallbacks(proxies, &wallets);
+    }
+}
+
+/// 用持久化的隔离状态刷新内存中的隔离集合
+fn sync_quarantined(proxies: &[ProxyIP]) {
+    let ids: HashSet<String> = proxies
+        .iter()
+        .filter(|p| p.status == "quarantined")
+        .map(|p| p.id.clone())
+        .collect();
+    proxy_health::set_quarantined(ids);
+}
+
+/// 将请求管道产生的健康事件写入代理列表，达到阈值的代理进入隔离
+///
+/// 写入失败时事件放回内存，下次再写
+pub fn apply_health_events(app: &tauri::AppHandle) -> Result<(), String> {
+    let events = proxy_health::take_events();
+    let result = update_proxies(app, |proxies| {
+        let now = chrono::Utc::now();
+
+        for event in &events {
+            let proxy = match proxies.iter_mut().find(|p| p.id == event.proxy_id) {
+                Some(p) => p,
+                None => continue,
+            };
+
+            match event.kind {
+                Some(kind) => {
+                    proxy.failure_count += 1;
+                    proxy.consecutive_failures += 1;
+                    proxy.last_failure_at = Some(event.at.clone());
+                    proxy.last_failure_reason = Some(kind);
+
+                    if proxy.consecutive_failures >= QUARANTINE_THRESHOLD
+                        && proxy.status != "quarantined"
+                        && quarantine(proxy, now)
+                    {
+                        let _ = app.emit("proxy_quarantined", proxy.clone());
+                    }
+                }
+                None => proxy.consecutive_failures = 0,
+            }
+        }
+
+        Ok(proxies.clone())
+    });
+
+    match result {
+        Ok(proxies) => {
+            sync_quarantined(&proxies);
+            Ok(())
+        }
+        Err(e) => {
+            proxy_health::restore_events(events);
+            Err(e)
+        }
+    }
+}
+
+/// 复检到期的隔离代理：通过则解除隔离，失败则加倍退避
+pub async fn recheck_quarantined(app: &tauri::AppHandle) -> Result<(), String> {
+    let now = chrono::Utc::now();
+    let due: Vec<ProxyIP> = load_proxies(app)?
+        .into_iter()
+        .filter(|p| p.status == "quarantined")
+        .filter(|p| {
+            p.quarantined_until
+                .as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .is_none_or(|t| t.with_timezone(&chrono::Utc) <= now)
+        })
+        .collect();
+
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    let mut results: HashMap<String, bool> = HashMap::new();
+    for proxy in &due {
+        results.insert(proxy.id.clone(), check_proxy_exit_ip(proxy).await.is_ok());
+    }
+
+    // 在写锁内重新读取后再写，避免覆盖检测期间的其它修改
+    let proxies = update_proxies(app, |proxies| {
+        let now = chrono::Utc::now();
+        // 检测期间被手动解除隔离、暂停或下线的代理不再处理
+        for idx in 0..proxies.len() {
+            if proxies[idx].status != "quarantined" {
+                continue;
+            }
+            match results.get(&proxies[idx].id) {
+                Some(true) => release(proxies, idx),
+                Some(false) => {
+                    quarantine(&mut proxies[idx], now);
+                }
+                None => {}
+            }
+        }
+        Ok(proxies.clone())
+    })?;
+    sync_quarantined(&proxies);
+
+    Ok(())
+}
+
+/// 手动解除隔离
+pub fn release_quarantine(app: &tauri::AppHandle, id: &str) -> Result<bool, String> {
+    let released = update_proxies(app, |proxies| {
+        let idx = match proxies.iter().position(|p| p.id == id) {
+            Some(i) => i,
+            None => return Ok(None),
+        };
+        if proxies[idx].status != "quarantined" {
+            return Ok(None);
+        }
+        release(proxies, idx);
+        Ok(Some(proxies.clone()))
+    })?;
+
+    match released {
+        Some(proxies) => {
+            sync_quarantined(&proxies);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// 启动后台健康监控任务
+pub fn spawn_health_monitor(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = apply_health_events(&app) {
+                eprintln!("Proxy health update error: {}", e);
+            }
+            if let Err(e) = recheck_quarantined(&app).await {
+                eprintln!("Proxy recheck error: {}", e);
+            }
+
+            tokio::time::sleep(Duration::from_secs(HEALTH_CHECK_INTERVAL_SECS)).await;
+        }
+    });
+}
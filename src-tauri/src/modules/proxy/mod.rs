@@ -1,5 +1,15 @@
 pub mod types;
 pub mod commands;
+pub mod utils;
+pub mod sources;
+pub mod traffic;
+pub mod health;
+pub mod exit_ip;
 
 pub use types::*;
 pub use commands::*;
+pub use utils::*;
+pub use sources::*;
+pub use traffic::*;
+pub use health::*;
+pub use exit_ip::*;
@@ -0,0 +1,397 @@
+use super::types::*;
+use super::utils::*;
+use crate::common::http::create_client;
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+/// 后台检查订阅是否到期的间隔
+const SYNC_CHECK_INTERVAL_SECS: u64 = 60;
+
+lazy_static! {
+    /// proxy_sources.json 的读-改-写锁（需要同时修改代理列表时先取本锁）
+    static ref SOURCES_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// 读取订阅源列表
+pub fn load_sources(app: &tauri::AppHandle) -> Result<Vec<ProxySource>, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let sources_path = data_dir.join("proxy_sources.json");
+
+    if !sources_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&sources_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_else(|_| Vec::new()))
+}
+
+/// 保存订阅源列表
+fn save_sources(app: &tauri::AppHandle, sources: &[ProxySource]) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    let sources_path = data_dir.join("proxy_sources.json");
+
+    let json = serde_json::to_string_pretty(sources).map_err(|e| e.to_string())?;
+    std::fs::write(&sources_path, json).map_err(|e| e.to_string())
+}
+
+/// 在写锁内读取、修改并保存订阅源列表，闭包返回错误时不落盘
+pub fn update_sources<R>(
+    app: &tauri::AppHandle,
+    f: impl FnOnce(&mut Vec<ProxySource>) -> Result<R, String>,
+) -> Result<R, String> {
+    let _guard = SOURCES_LOCK.lock().unwrap();
+    let mut sources = load_sources(app)?;
+    let result = f(&mut sources)?;
+    save_sources(app, &sources)?;
+    Ok(result)
+}
+
+/// 拉取订阅内容
+pub async fn fetch_source_text(source: &ProxySource) -> Result<String, String> {
+    match source.kind {
+        ProxySourceKind::Url => {
+            let client = create_client(None)?;
+            let response = client
+                .get(&source.location)
+                .timeout(Duration::from_secs(30))
+                .send()
+                .await
+                .map_err(|e| format!("拉取订阅失败: {}", e))?;
+
+            if !response.status().is_success() {
+                return Err(format!("拉取订阅失败: HTTP {}", response.status()));
+            }
+
+            response
+                .text()
+                .await
+                .map_err(|e| format!("读取订阅内容失败: {}", e))
+        }
+        ProxySourceKind::File => std::fs::read_to_string(&source.location)
+            .map_err(|e| format!("读取订阅文件失败: {}", e)),
+    }
+}
+
+/// 将订阅内容合并进代理列表
+///
+/// 新代理加入，消失的代理标记为 retired（保留钱包绑定以便重新分配），
+/// 重新出现的 retired 代理恢复为 inactive。
+pub fn merge_source_proxies(
+    proxies: &mut Vec<ProxyIP>,
+    source: &ProxySource,
+    text: &str,
+) -> ProxySyncReport {
+    let mut report = ProxySyncReport {
+        source_id: source.id.clone(),
+        source_name: source.name.clone(),
+        added: Vec::new(),
+        retired: Vec::new(),
+        restored: Vec::new(),
+        unchanged: 0,
+        invalid_lines: 0,
+        orphaned_bindings: Vec::new(),
+        synced_at: chrono::Utc::now().to_rfc3339(),
+        error: None,
+    };
+
+    // 解析并按 ip:port 去重
+    let mut listed: HashMap<String, ParsedProxy> = HashMap::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        match parse_proxy_line(trimmed, source.format, &source.default_protocol) {
+            Some(parsed) => {
+                listed.insert(format!("{}:{}", parsed.ip, parsed.port), parsed);
+            }
+            None => report.invalid_lines += 1,
+        }
+    }
+
+    let mut seen: HashSet<String> = HashSet::new();
+
+    for proxy in proxies.iter_mut() {
+        let key = format!("{}:{}", proxy.ip, proxy.port);
+
+        if let Some(parsed) = listed.get(&key) {
+            seen.insert(key.clone());
+
+            // 手动添加的同地址代理不归订阅管理
+            if proxy.source_id.as_deref() != Some(source.id.as_str()) {
+                report.unchanged += 1;
+                continue;
+            }
+
+            proxy.protocol = parsed.protocol.clone();
+            proxy.username = parsed.username.clone();
+            proxy.password = parsed.password.clone();
+
+            if proxy.status == "retired" {
+                proxy.status = "inactive".to_string();
+                report.restored.push(key);
+            } else {
+                report.unchanged += 1;
+            }
+        } else if proxy.source_id.as_deref() == Some(source.id.as_str())
+            && proxy.status != "retired"
+        {
+            proxy.status = "retired".to_string();
+            if !proxy.wallet_bindings.is_empty() {
+                report.orphaned_bindings.push(key.clone());
+            }
+            report.retired.push(key);
+        }
+    }
+
+    // 按订阅原始顺序加入新代理
+    for line in text.lines() {
+        let parsed = match parse_proxy_line(line, source.format, &source.default_protocol) {
+            Some(p) => p,
+            None => continue,
+        };
+        let key = format!("{}:{}", parsed.ip, parsed.port);
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+
+        let new_proxy = ProxyIP {
+            id: next_proxy_id(proxies),
+            ip: parsed.ip,
+            port: parsed.port,
+            protocol: parsed.protocol,
+            username: parsed.username,
+            password: parsed.password,
+            country: None,
+            status: "inactive".to_string(),
+            wallet_bindings: Vec::new(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            last_used: None,
+            session_mode: ProxySessionMode::default(),
+            sticky_sessions: HashMap::new(),
+            source_id: Some(source.id.clone()),
+            daily_budget_mb: None,
+            ..Default::default()
+        };
+        proxies.push(new_proxy);
+        report.added.push(key);
+    }
+
+    report
+}
+
+/// 同步单个订阅源并落盘
+pub async fn sync_source(
+    app: &tauri::AppHandle,
+    source_id: &str,
+) -> Result<ProxySyncReport, String> {
+    let sources = load_sources(app)?;
+    let source = sources
+        .iter()
+        .find(|s| s.id == source_id)
+        .cloned()
+        .ok_or_else(|| "找不到指定的订阅源".to_string())?;
+
+    let fetched = fetch_source_text(&source).await;
+
+    // 在锁内重新读取，避免覆盖拉取期间的其它修改；拉取期间被删除的订阅不再合并
+    let report = update_sources(app, |sources| {
+        let entry = sources
+            .iter_mut()
+            .find(|s| s.id == source_id)
+            .ok_or_else(|| "订阅源已在同步期间被删除".to_string())?;
+
+        match fetched {
+            Ok(text) => {
+                let current = entry.clone();
+                let report =
+                    update_proxies(app, |proxies| Ok(merge_source_proxies(proxies, &current, &text)))?;
+                entry.last_synced_at = Some(report.synced_at.clone());
+                entry.last_error = None;
+                Ok(Ok(report))
+            }
+            Err(e) => {
+                entry.last_synced_at = Some(chrono::Utc::now().to_rfc3339());
+                entry.last_error = Some(e.clone());
+                Ok(Err(e))
+            }
+        }
+    })??;
+
+    let _ = app.emit("proxy_source_synced", report.clone());
+
+    Ok(report)
+}
+
+/// 判断订阅是否到了同步时间
+fn is_source_due(source: &ProxySource, now: chrono::DateTime<chrono::Utc>) -> bool {
+    if !source.enabled || source.refresh_interval_secs == 0 {
+        return false;
+    }
+
+    match source
+        .last_synced_at
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+    {
+        Some(last) => {
+            let elapsed = now.signed_duration_since(last.with_timezone(&chrono::Utc));
+            elapsed.num_seconds() >= source.refresh_interval_secs as i64
+        }
+        None => true,
+    }
+}
+
+/// 启动后台定时同步任务
+pub fn spawn_source_sync(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Ok(sources) = load_sources(&app) {
+                let now = chrono::Utc::now();
+                for source in sources.iter().filter(|s| is_source_due(s, now)) {
+                    if let Err(e) = sync_source(&app, &source.id).await {
+                        eprintln!("Proxy source {} sync error: {}", source.name, e);
+                    }
+                }
+            }
+
+            tokio::time::sleep(Duration::from_secs(SYNC_CHECK_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn source(kind: ProxySourceKind, location: &str, format: ProxyListFormat) -> ProxySource {
+        ProxySource {
+            id: "src-1".to_string(),
+            name: "test".to_string(),
+            kind,
+            location: location.to_string(),
+            format,
+            default_protocol: "http".to_string(),
+            refresh_interval_secs: 0,
+            enabled: true,
+            created_at: String::new(),
+            last_synced_at: None,
+            last_error: None,
+        }
+    }
+
+    /// 启动只响应一次的本地 HTTP 服务，返回订阅地址
+    async fn serve_once(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = socket.read(&mut buf).await;
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{}/proxies.txt", addr)
+    }
+
+    #[test]
+    fn merge_adds_new_proxies_and_counts_invalid_lines() {
+        let src = source(ProxySourceKind::File, "", ProxyListFormat::Url);
+        let mut proxies = Vec::new();
+
+        let report = merge_source_proxies(
+            &mut proxies,
+            &src,
+            "http://u:p@1.1.1.1:80\n# comment\n2.2.2.2:81\nnot a proxy\n2.2.2.2:81\n",
+        );
+
+        assert_eq!(report.added, vec!["1.1.1.1:80", "2.2.2.2:81"]);
+        assert_eq!(report.invalid_lines, 1);
+        assert_eq!(proxies.len(), 2);
+        assert_eq!(proxies[0].id, "1");
+        assert_eq!(proxies[1].id, "2");
+        assert_eq!(proxies[0].source_id.as_deref(), Some("src-1"));
+        assert_eq!(proxies[0].username.as_deref(), Some("u"));
+    }
+
+    #[test]
+    fn merge_retires_missing_and_restores_returning_proxies() {
+        let src = source(ProxySourceKind::File, "", ProxyListFormat::Url);
+        let mut proxies = Vec::new();
+        merge_source_proxies(&mut proxies, &src, "1.1.1.1:80\n2.2.2.2:81\n");
+        proxies[0].wallet_bindings.push("0xabc".to_string());
+
+        let report = merge_source_proxies(&mut proxies, &src, "2.2.2.2:81\n3.3.3.3:82\n");
+        assert_eq!(report.retired, vec!["1.1.1.1:80"]);
+        assert_eq!(report.orphaned_bindings, vec!["1.1.1.1:80"]);
+        assert_eq!(report.added, vec!["3.3.3.3:82"]);
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(proxies[0].status, "retired");
+        // 下线的代理保留钱包绑定，便于重新分配
+        assert_eq!(proxies[0].wallet_bindings, vec!["0xabc"]);
+
+        let report = merge_source_proxies(&mut proxies, &src, "1.1.1.1:80\n");
+        assert_eq!(report.restored, vec!["1.1.1.1:80"]);
+        assert_eq!(report.retired.len(), 2);
+        assert_eq!(proxies[0].status, "inactive");
+    }
+
+    #[test]
+    fn merge_leaves_manual_proxies_alone() {
+        let src = source(ProxySourceKind::File, "", ProxyListFormat::Url);
+        let mut proxies = vec![ProxyIP {
+            id: "7".to_string(),
+            ip: "1.1.1.1".to_string(),
+            port: 80,
+            protocol: "socks5".to_string(),
+            status: "active".to_string(),
+            ..Default::default()
+        }];
+
+        let report = merge_source_proxies(&mut proxies, &src, "http://u:p@1.1.1.1:80\n");
+        assert!(report.added.is_empty());
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(proxies[0].protocol, "socks5");
+        assert_eq!(proxies[0].username, None);
+
+        // 订阅里消失也不会下线手动添加的代理
+        let report = merge_source_proxies(&mut proxies, &src, "");
+        assert!(report.retired.is_empty());
+        assert_eq!(proxies[0].status, "active");
+    }
+
+    #[tokio::test]
+    async fn syncs_url_source_from_local_server() {
+        let url = serve_once("200 OK", "10.0.0.1:8000:user:pass\n10.0.0.2:8001\n").await;
+        let src = source(ProxySourceKind::Url, &url, ProxyListFormat::IpPortUserPass);
+
+        let text = fetch_source_text(&src).await.unwrap();
+        let mut proxies = Vec::new();
+        let report = merge_source_proxies(&mut proxies, &src, &text);
+
+        assert_eq!(report.added, vec!["10.0.0.1:8000", "10.0.0.2:8001"]);
+        assert_eq!(proxies[0].protocol, "http");
+        assert_eq!(proxies[0].password.as_deref(), Some("pass"));
+        assert_eq!(proxies[1].username, None);
+    }
+
+    #[tokio::test]
+    async fn sync_reports_http_errors() {
+        let url = serve_once("503 Service Unavailable", "").await;
+        let src = source(ProxySourceKind::Url, &url, ProxyListFormat::Url);
+
+        let err = fetch_source_text(&src).await.unwrap_err();
+        assert!(err.contains("503"), "{}", err);
+    }
+}
@@ -0,0 +1,113 @@
+use super::utils::*;
+use crate::common::traffic::{self, TrafficRecord, TrafficScope};
+use lazy_static::lazy_static;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+lazy_static! {
+    /// traffic_usage.json 写锁，取增量、读取、写回在同一把锁内完成
+    static ref TRAFFIC_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// 流量增量落盘间隔
+const TRAFFIC_FLUSH_INTERVAL_SECS: u64 = 30;
+
+/// 读取已落盘的流量记录
+pub fn load_traffic(app: &tauri::AppHandle) -> Result<Vec<TrafficRecord>, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let traffic_path = data_dir.join("traffic_usage.json");
+
+    if !traffic_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&traffic_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_else(|_| Vec::new()))
+}
+
+fn save_traffic(app: &tauri::AppHandle, records: &[TrafficRecord]) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    let traffic_path = data_dir.join("traffic_usage.json");
+
+    let json = serde_json::to_string_pretty(records).map_err(|e| e.to_string())?;
+    std::fs::write(&traffic_path, json).map_err(|e| e.to_string())
+}
+
+/// 将内存中的流量增量写入磁盘，并按每日预算暂停/恢复代理
+pub fn flush_traffic(app: &tauri::AppHandle) -> Result<(), String> {
+    let records = {
+        let _guard = TRAFFIC_LOCK.lock().unwrap();
+        let pending = traffic::take_pending();
+
+        let mut records = match load_traffic(app) {
+            Ok(r) => r,
+            Err(e) => {
+                traffic::restore_pending(pending);
+                return Err(e);
+            }
+        };
+        if !pending.is_empty() {
+            traffic::merge_records(&mut records, pending.clone());
+            if let Err(e) = save_traffic(app, &records) {
+                // 写入失败时放回增量，避免丢失统计
+                traffic::restore_pending(pending);
+                return Err(e);
+            }
+        }
+        records
+    };
+
+    enforce_budgets(app, &records)
+}
+
+/// 检查代理今日流量是否超出预算
+fn enforce_budgets(app: &tauri::AppHandle, records: &[TrafficRecord]) -> Result<(), String> {
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let mut paused = HashSet::new();
+
+    update_proxies(app, |proxies| {
+        for proxy in proxies.iter_mut() {
+            let used_bytes: u64 = records
+                .iter()
+                .filter(|r| r.date == today && r.scope == TrafficScope::Proxy && r.key == proxy.id)
+                .map(|r| r.total_bytes())
+                .sum();
+
+            let over_budget = match proxy.daily_budget_mb {
+                Some(budget) if budget > 0.0 => used_bytes as f64 / 1_048_576.0 >= budget,
+                _ => false,
+            };
+
+            if over_budget {
+                paused.insert(proxy.id.clone());
+                if !matches!(proxy.status.as_str(), "paused" | "retired" | "quarantined") {
+                    proxy.status = "paused".to_string();
+                }
+            } else if proxy.status == "paused" {
+                // 新的一天或预算调高后恢复
+                proxy.status = "inactive".to_string();
+            }
+        }
+        Ok(())
+    })?;
+
+    traffic::set_paused_proxies(paused);
+
+    Ok(())
+}
+
+/// 启动后台流量落盘任务
+pub fn spawn_traffic_flush(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = flush_traffic(&app) {
+                eprintln!("Traffic flush error: {}", e);
+            }
+
+            tokio::time::sleep(Duration::from_secs(TRAFFIC_FLUSH_INTERVAL_SECS)).await;
+        }
+    });
+}
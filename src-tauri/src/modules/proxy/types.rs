@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
+use crate::common::http::ProxySession;
+use crate::common::proxy_health::ProxyFailureKind;
+use std::collections::HashMap;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ProxyIP {
     pub id: String,
     pub ip: String,
@@ -13,4 +16,168 @@ pub struct ProxyIP {
     pub wallet_bindings: Vec<String>,
     pub created_at: String,
     pub last_used: Option<String>,
+    /// 会话模式（住宅代理网关的用户名/密码中可使用 {session}、{country} 占位符）
+    #[serde(default)]
+    pub session_mode: ProxySessionMode,
+    /// 粘性会话：钱包地址(小写) -> 会话ID
+    #[serde(default)]
+    pub sticky_sessions: HashMap<String, String>,
+    /// 来源订阅ID（手动添加的代理为 None）
+    #[serde(default)]
+    pub source_id: Option<String>,
+    /// 每日流量预算（MB），超出后代理状态置为 paused
+    #[serde(default)]
+    pub daily_budget_mb: Option<f64>,
+    /// 健康统计（由真实请求失败累计）
+    #[serde(default)]
+    pub failure_count: u64,
+    #[serde(default)]
+    pub consecutive_failures: u32,
+    #[serde(default)]
+    pub last_failure_at: Option<String>,
+    #[serde(default)]
+    pub last_failure_reason: Option<ProxyFailureKind>,
+    /// 隔离级别（每次复检失败 +1，退避时间翻倍）
+    #[serde(default)]
+    pub quarantine_level: u32,
+    #[serde(default)]
+    pub quarantined_until: Option<String>,
+    /// 作为备用代理分配给了哪个钱包
+    #[serde(default)]
+    pub fallback_for: Option<String>,
+}
+
+/// 钱包绑定代理不可用时的备用策略
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyFallbackPolicy {
+    #[default]
+    None,        // 不切换，任务直接失败
+    SameCountry, // 切换到同国家的空闲代理（不与其它钱包共用）
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxySessionMode {
+    #[default]
+    Static,   // 固定凭据
+    Sticky,   // 每个钱包保持同一会话
+    Rotating, // 每个任务使用新会话
+}
+
+/// 钱包解析到的代理
+#[derive(Debug, Clone)]
+pub struct WalletProxy {
+    pub proxy_id: String,
+    pub url: String,
+    pub session: ProxySession,
+}
+
+/// 代理订阅源（服务商发布的代理列表）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProxySource {
+    pub id: String,
+    pub name: String,
+    pub kind: ProxySourceKind,
+    /// URL 或本地文件路径
+    pub location: String,
+    pub format: ProxyListFormat,
+    /// 列表中未写协议时使用的默认协议
+    pub default_protocol: String,
+    /// 自动同步间隔（秒），0 表示只手动同步
+    pub refresh_interval_secs: u64,
+    pub enabled: bool,
+    pub created_at: String,
+    pub last_synced_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxySourceKind {
+    Url,
+    File,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyListFormat {
+    IpPortProtocol,   // ip:port:protocol[:username:password]（与批量导入一致）
+    IpPortUserPass,   // ip:port[:username:password]
+    Url,              // protocol://username:password@ip:port
+}
+
+/// 一次同步的变更结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProxySyncReport {
+    pub source_id: String,
+    pub source_name: String,
+    pub added: Vec<String>,
+    pub retired: Vec<String>,
+    pub restored: Vec<String>,
+    pub unchanged: usize,
+    pub invalid_lines: usize,
+    /// 已下线但仍绑定钱包的代理（需要重新分配）
+    pub orphaned_bindings: Vec<String>,
+    pub synced_at: String,
+    /// 同步失败原因（批量同步时失败的订阅也返回一条报告）
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// 钱包出口IP观测记录（同一钱包+代理+IP 合并为一条）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExitIpObservation {
+    pub wallet: String,
+    pub proxy_id: Option<String>,
+    /// 观测时的代理会话ID（粘性会话），用于判断出口是否可能已变化
+    #[serde(default)]
+    pub session_id: Option<String>,
+    pub ip: String,
+    /// 例如 "AS15169 Google LLC"
+    pub asn: Option<String>,
+    pub source: ExitIpSource,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub times_seen: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitIpSource {
+    HealthCheck,
+    Run,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    Ip,
+    Subnet, // IPv4 /24，IPv6 /48
+    Asn,
+}
+
+/// 一组共用同一出口IP / 网段 / ASN 的钱包
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WalletLink {
+    pub kind: LinkKind,
+    pub value: String,
+    pub wallets: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SybilLinkReport {
+    pub generated_at: String,
+    pub wallets_checked: usize,
+    pub links: Vec<WalletLink>,
+}
+
+/// 计划中的钱包→代理映射会产生的新关联
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlannedLinkWarning {
+    pub wallet: String,
+    pub proxy_id: String,
+    pub kind: LinkKind,
+    pub value: String,
+    pub linked_wallets: Vec<String>,
 }
@@ -0,0 +1,100 @@
+use crate::common::captcha::{
+    CaptchaError, CaptchaErrorKind, CaptchaService, CaptchaSolution, CaptchaTask, CaptchaUsageTags,
+    TokenPool, TokenPoolConfig,
+};
+use crate::modules::system::captcha_service;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// 一次运行共享的打码服务和预解池
+///
+/// 首次打码时按当前打码设置创建服务，同一运行内的钱包共用；
+/// 花费记在运行ID下，日预算和单次运行预算由服务检查。
+/// 预解池按任务首次取令牌时启动，运行结束时由 `stop` 停止
+#[derive(Clone)]
+pub struct RunCaptcha {
+    script_id: String,
+    run_id: Option<String>,
+    /// 运行的并发钱包数，决定预解池的预热数量
+    concurrency: usize,
+    service: Arc<Mutex<Option<CaptchaService>>>,
+    /// 按任务（序列化后）区分的预解池
+    pools: Arc<Mutex<HashMap<String, TokenPool>>>,
+}
+
+impl RunCaptcha {
+    pub fn new(script_id: &str, run_id: Option<&str>, concurrency: usize) -> Self {
+        Self {
+            script_id: script_id.to_string(),
+            run_id: run_id.map(str::to_string),
+            concurrency: concurrency.max(1),
+            service: Arc::new(Mutex::new(None)),
+            pools: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn service(&self, app: &tauri::AppHandle) -> Result<CaptchaService, CaptchaError> {
+        let mut service = self.service.lock().unwrap();
+        if service.is_none() {
+            *service = Some(captcha_service(app).map_err(|e| {
+                CaptchaError::new(CaptchaErrorKind::NotConfigured, None, e)
+            })?);
+        }
+        Ok(service.clone().unwrap())
+    }
+
+    pub fn tags(&self, wallet: Option<&str>) -> CaptchaUsageTags {
+        CaptchaUsageTags {
+            script: Some(self.script_id.clone()),
+            wallet: wallet.map(str::to_string),
+            run_id: self.run_id.clone(),
+        }
+    }
+
+    /// 为某个钱包打码
+    pub async fn solve(
+        &self,
+        app: &tauri::AppHandle,
+        task: &CaptchaTask,
+        wallet: &str,
+    ) -> Result<CaptchaSolution, CaptchaError> {
+        let service = self.service(app)?;
+        service.solve(task, &self.tags(Some(wallet))).await
+    }
+
+    /// 从预解池取一个令牌，池不存在时按运行并发数启动
+    ///
+    /// 池中的令牌不属于某个钱包，花费只记在运行下
+    pub async fn pooled(
+        &self,
+        app: &tauri::AppHandle,
+        task: &CaptchaTask,
+    ) -> Result<CaptchaSolution, CaptchaError> {
+        let key = serde_json::to_string(task).unwrap_or_default();
+        let pool = {
+            let mut pools = self.pools.lock().unwrap();
+            match pools.get(&key) {
+                Some(pool) => pool.clone(),
+                None => {
+                    let config = TokenPoolConfig {
+                        task: task.clone(),
+                        ttl: None,
+                        concurrency: self.concurrency,
+                        max_size: self.concurrency * 2,
+                    };
+                    let pool = TokenPool::start(self.service(app)?, config, self.tags(None))?;
+                    pools.insert(key, pool.clone());
+                    pool
+                }
+            }
+        };
+        pool.acquire().await
+    }
+
+    /// 停止本次运行的所有预解池
+    pub fn stop(&self) {
+        for (_, pool) in self.pools.lock().unwrap().drain() {
+            pool.stop();
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use super::registry;
+use super::presets::preset_config;
+use super::runner;
+use super::history::*;
+use super::types::*;
+use serde_json::Value;
+
+/// 获取所有已注册脚本
+#[tauri::command]
+pub async fn list_scripts() -> Result<Vec<ScriptMeta>, String> {
+    Ok(registry::list())
+}
+
+/// 启动脚本，返回运行ID；未传配置时使用预设或脚本默认配置
+#[tauri::command]
+pub async fn start_script(
+    app: tauri::AppHandle,
+    script_id: String,
+    config: Option<Value>,
+    preset: Option<String>,
+    options: Option<RunOptions>,
+) -> Result<String, String> {
+    let config = match (config, preset) {
+        (Some(config), _) => config,
+        (None, Some(name)) => preset_config(&app, &script_id, &name)?,
+        (None, None) => Value::Object(Default::default()),
+    };
+    runner::start_run(app, &script_id, config, options.unwrap_or_default()).await
+}
+
+/// 停止脚本
+#[tauri::command]
+pub async fn stop_script(script_id: String) -> Result<(), String> {
+    runner::stop_run(&script_id).await
+}
+
+/// 获取脚本运行状态
+#[tauri::command]
+pub async fn script_status(script_id: String) -> Result<ScriptStatus, String> {
+    runner::run_status(&script_id).await
+}
+
+/// 应用中途退出时运行记录停留在 running，读取时标记为中断
+async fn mark_interrupted(mut record: RunRecord) -> RunRecord {
+    if record.status == RunOutcome::Running && !runner::is_run_active(&record.run_id).await {
+        record.status = RunOutcome::Interrupted;
+    }
+    record
+}
+
+/// 运行历史（按开始时间倒序），可按脚本过滤
+#[tauri::command]
+pub async fn list_script_runs(
+    app: tauri::AppHandle,
+    script_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<RunSummary>, String> {
+    let mut summaries = Vec::new();
+    for record in load_run_records(&app)? {
+        if script_id.as_ref().is_some_and(|id| *id != record.script_id) {
+            continue;
+        }
+        if limit.is_some_and(|limit| summaries.len() >= limit) {
+            break;
+        }
+        summaries.push(mark_interrupted(record).await.summary());
+    }
+    Ok(summaries)
+}
+
+/// 获取一次运行的完整记录
+#[tauri::command]
+pub async fn get_script_run(app: tauri::AppHandle, run_id: String) -> Result<RunRecord, String> {
+    Ok(mark_interrupted(load_run_record(&app, &run_id)?).await)
+}
+
+/// 对比两次运行
+#[tauri::command]
+pub async fn diff_script_runs(
+    app: tauri::AppHandle,
+    base_run_id: String,
+    other_run_id: String,
+) -> Result<RunDiff, String> {
+    let base = mark_interrupted(load_run_record(&app, &base_run_id)?).await;
+    let other = mark_interrupted(load_run_record(&app, &other_run_id)?).await;
+    Ok(diff_runs(&base, &other))
+}
+
+/// 导出一次运行为 CSV 文本（每个钱包每个步骤一行）
+#[tauri::command]
+pub async fn export_script_run_csv(app: tauri::AppHandle, run_id: String) -> Result<String, String> {
+    Ok(run_to_csv(&load_run_record(&app, &run_id)?))
+}
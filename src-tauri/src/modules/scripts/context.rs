@@ -0,0 +1,500 @@
+use super::captcha::RunCaptcha;
+use super::history::{ErrorClass, StepOutcome, StepStatus};
+use super::ledger::{self, LedgerEntry};
+use super::logs;
+use super::queue::{self, TxState, WorkStatus};
+use super::types::*;
+use crate::common::captcha::{CaptchaError, CaptchaErrorKind, CaptchaSolution, CaptchaTask};
+use crate::common::http::HttpClient;
+use crate::common::rpc::create_rpc_client;
+use alloy::network::{EthereumWallet, Network, ReceiptResponse};
+use alloy::providers::{
+    DynProvider, PendingTransactionBuilder, PendingTransactionError, Provider, ProviderBuilder,
+};
+use alloy::signers::local::PrivateKeySigner;
+use rand::rngs::StdRng;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::future::IntoFuture;
+use std::time::Duration;
+use tauri::Emitter;
+use tokio::sync::Notify;
+
+/// 操作因停止信号中断
+#[derive(Debug, Clone, Copy)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cancelled")
+    }
+}
+
+impl From<Cancelled> for String {
+    fn from(e: Cancelled) -> Self {
+        e.to_string()
+    }
+}
+
+/// 停止信号，克隆共享
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<CancelInner>,
+}
+
+#[derive(Default)]
+struct CancelInner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Relaxed);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// 等待停止信号
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// 执行 future，收到停止信号时丢弃它并返回 `Cancelled`
+    pub async fn run_until_cancelled<F: IntoFuture>(&self, fut: F) -> Result<F::Output, Cancelled> {
+        tokio::select! {
+            biased;
+            _ = self.cancelled() => Err(Cancelled),
+            output = fut => Ok(output),
+        }
+    }
+}
+
+/// 脚本日志，发送 `script_log` 事件（及脚本声明的旧事件）并写入运行日志
+///
+/// 消息在发出前脱敏，钱包私钥等已知密钥通过 `add_secret` 登记
+#[derive(Clone)]
+pub struct ScriptLogger {
+    app: tauri::AppHandle,
+    script_id: String,
+    run_id: Option<String>,
+    address: String,
+    legacy_event: Option<&'static str>,
+    /// 当前步骤，由 `ScriptContext::begin_step` 设置
+    step: Arc<Mutex<Option<String>>>,
+    secrets: Arc<Mutex<Vec<String>>>,
+}
+
+impl ScriptLogger {
+    pub fn new(app: &tauri::AppHandle, script: &dyn Script, address: &str) -> Self {
+        Self {
+            app: app.clone(),
+            script_id: script.meta().id,
+            run_id: None,
+            address: address.to_string(),
+            legacy_event: script.legacy_log_event(),
+            step: Arc::new(Mutex::new(None)),
+            secrets: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn with_run(mut self, run_id: Option<&str>) -> Self {
+        self.run_id = run_id.map(str::to_string);
+        self
+    }
+
+    /// 同一脚本下另一个地址的日志（不共享步骤和密钥）
+    pub fn for_address(&self, address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            step: Arc::new(Mutex::new(None)),
+            secrets: Arc::new(Mutex::new(Vec::new())),
+            ..self.clone()
+        }
+    }
+
+    /// 登记需要从日志中屏蔽的密钥
+    pub fn add_secret(&self, secret: &str) {
+        self.secrets.lock().unwrap().push(secret.to_string());
+    }
+
+    pub fn set_step(&self, step: Option<&str>) {
+        *self.step.lock().unwrap() = step.map(str::to_string);
+    }
+
+    pub fn emit(&self, message: &str, level: LogLevel) {
+        let message = logs::redact(message, &self.secrets.lock().unwrap());
+        let event = ScriptLogEvent {
+            script_id: self.script_id.clone(),
+            run_id: self.run_id.clone(),
+            address: self.address.clone(),
+            step: self.step.lock().unwrap().clone(),
+            message,
+            level,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+        };
+        if let Some(legacy) = self.legacy_event {
+            let _ = self.app.emit(legacy, event.clone());
+        }
+        let _ = self.app.emit("script_log", event.clone());
+        logs::publish(&self.app, &event);
+    }
+
+    pub fn debug(&self, message: &str) {
+        self.emit(message, LogLevel::Debug);
+    }
+
+    pub fn info(&self, message: &str) {
+        self.emit(message, LogLevel::Info);
+    }
+
+    pub fn success(&self, message: &str) {
+        self.emit(message, LogLevel::Success);
+    }
+
+    pub fn warn(&self, message: &str) {
+        self.emit(message, LogLevel::Warn);
+    }
+
+    pub fn error(&self, message: &str) {
+        self.emit(message, LogLevel::Error);
+    }
+}
+
+/// 单个钱包的执行上下文
+pub struct ScriptContext {
+    pub app: tauri::AppHandle,
+    pub script_id: String,
+    pub run_id: Option<String>,
+    pub address: String,
+    pub config: Value,
+    pub signer: PrivateKeySigner,
+    /// 钱包的 HTTP 客户端（绑定代理、指纹、cookie）
+    pub http: HttpClient,
+    pub log: ScriptLogger,
+    pub cancel: CancelToken,
+    pub(super) captcha: RunCaptcha,
+    /// 由运行种子和钱包地址派生，同一种子下结果可复现
+    pub(super) rng: Mutex<StdRng>,
+    pub(super) step_delay: DelayRange,
+    /// 本次运行的项目日（见 `Script::daily_reset_minutes`）
+    pub(super) ledger_day: String,
+    pub(super) force: bool,
+    /// 恢复运行前本次运行已完成的步骤次数（步骤 → 次数）
+    pub(super) resumed: HashMap<String, u32>,
+    /// 已广播、尚未等到回执的交易
+    pub(super) pending_txs: Mutex<Vec<String>>,
+    /// 按开始顺序记录的步骤结果，最后一个为当前步骤
+    pub(super) steps: Mutex<Vec<StepOutcome>>,
+    /// 运行前后的积分
+    pub(super) points: Mutex<(Option<u64>, Option<u64>)>,
+}
+
+impl ScriptContext {
+    /// 按脚本的配置类型解析配置
+    pub fn config<T: DeserializeOwned>(&self) -> Result<T, String> {
+        serde_json::from_value(self.config.clone()).map_err(|e| format!("Invalid script config: {}", e))
+    }
+
+    /// 本钱包的随机数发生器，脚本内的随机选择和金额都应从这里取（不要跨 await 持有）
+    pub fn rng(&self) -> MutexGuard<'_, StdRng> {
+        self.rng.lock().unwrap()
+    }
+
+    /// 已收到停止信号时返回 `Cancelled`，用于步骤和循环开头
+    pub fn check_cancelled(&self) -> Result<(), Cancelled> {
+        if self.cancel.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 可被停止信号中断的 HTTP/RPC 调用
+    pub async fn run_until_cancelled<F: IntoFuture>(&self, fut: F) -> Result<F::Output, Cancelled> {
+        self.cancel.run_until_cancelled(fut).await
+    }
+
+    /// 等待交易回执，收到停止信号时立即返回
+    ///
+    /// 交易在等待期间记为待确认，中断时留在结果的 `pending_txs` 中。
+    /// 发送交易本身不做中断，避免无法判断交易是否已广播
+    pub async fn wait_receipt<N: Network>(
+        &self,
+        pending: PendingTransactionBuilder<N>,
+    ) -> Result<Result<N::ReceiptResponse, PendingTransactionError>, Cancelled> {
+        self.wait_tx(pending, false).await
+    }
+
+    /// 等待辅助交易（如代币授权）的回执，该交易不代表步骤完成，恢复运行时不写台账
+    pub async fn wait_auxiliary_receipt<N: Network>(
+        &self,
+        pending: PendingTransactionBuilder<N>,
+    ) -> Result<Result<N::ReceiptResponse, PendingTransactionError>, Cancelled> {
+        self.wait_tx(pending, true).await
+    }
+
+    async fn wait_tx<N: Network>(
+        &self,
+        pending: PendingTransactionBuilder<N>,
+        auxiliary: bool,
+    ) -> Result<Result<N::ReceiptResponse, PendingTransactionError>, Cancelled> {
+        let tx_hash = pending.tx_hash().to_string();
+        self.pending_txs.lock().unwrap().push(tx_hash.clone());
+
+        let mut step_name = String::from("tx");
+        self.with_current_step(|step| {
+            step.tx_hashes.push(tx_hash.clone());
+            step_name = step.step.clone();
+        });
+        self.update_queue(|run_id| {
+            queue::add_tx(&self.app, run_id, &self.address, &step_name, &tx_hash, auxiliary)
+        });
+
+        // 中断或取不到回执时交易在队列中保持待确认，恢复运行前到链上核对
+        let receipt = self.run_until_cancelled(pending.get_receipt()).await?;
+        self.pending_txs.lock().unwrap().retain(|h| *h != tx_hash);
+        match &receipt {
+            Ok(r) if !r.status() => {
+                self.note_step_error(ErrorClass::Reverted, &format!("Tx {} reverted", tx_hash));
+                self.update_queue(|run_id| {
+                    queue::set_tx_state(&self.app, run_id, &tx_hash, TxState::Reverted)
+                });
+            }
+            Ok(_) => self.update_queue(|run_id| {
+                queue::set_tx_state(&self.app, run_id, &tx_hash, TxState::Confirmed)
+            }),
+            Err(e) => self.note_step_error(ErrorClass::Receipt, &e.to_string()),
+        }
+        Ok(receipt)
+    }
+
+    /// 同步到运行的工作队列（单独执行的钱包没有队列）
+    fn update_queue(&self, f: impl FnOnce(&str) -> Result<(), String>) {
+        if let Some(run_id) = &self.run_id {
+            if let Err(e) = f(run_id) {
+                self.log.warn(&format!("Failed to update work queue: {}", e));
+            }
+        }
+    }
+
+    pub(super) fn take_pending_txs(&self) -> Vec<String> {
+        std::mem::take(&mut *self.pending_txs.lock().unwrap())
+    }
+
+    fn with_current_step(&self, f: impl FnOnce(&mut StepOutcome)) {
+        if let Some(step) = self
+            .steps
+            .lock()
+            .unwrap()
+            .last_mut()
+            .filter(|s| s.status == StepStatus::Running)
+        {
+            f(step);
+        }
+    }
+
+    /// 开始记录一个步骤，`target` 为本次需要完成的次数
+    ///
+    /// 步骤内的交易哈希、回执失败和 `record_completed` 自动计入当前步骤
+    pub fn begin_step(&self, step: &str, target: u32) {
+        self.end_step(Ok(()));
+        self.steps.lock().unwrap().push(StepOutcome::new(step, target));
+        self.log.set_step(Some(step));
+        // 轮换代理每个任务使用新会话
+        if let Err(e) = self.http.rotate_session() {
+            self.log.warn(&format!("Failed to rotate proxy session: {}", e));
+        }
+        // 队列中的次数含恢复前已完成的，再次恢复时据此跳过
+        let base = self.resumed_done(step);
+        self.update_queue(|run_id| {
+            queue::mark_step(&self.app, run_id, &self.address, step, WorkStatus::Running, base + target, base)
+        });
+    }
+
+    /// 记录当前步骤的错误（后记录的覆盖先前的）
+    pub fn note_step_error(&self, class: ErrorClass, message: &str) {
+        self.with_current_step(|step| {
+            step.error_class = Some(class);
+            step.error = Some(message.to_string());
+        });
+    }
+
+    /// 当前步骤的一次完成（不写台账，如登录）
+    pub fn step_done(&self) {
+        self.with_current_step(|step| step.done += 1);
+    }
+
+    /// 结束当前步骤，`Err` 表示步骤中止（未记录错误时以该消息作为错误）
+    pub fn end_step(&self, result: Result<(), &str>) {
+        let cancelled = self.cancel.is_cancelled();
+        let mut ended = None;
+        self.with_current_step(|step| {
+            if let Err(message) = result {
+                if step.error.is_none() {
+                    step.error_class = Some(ErrorClass::Other);
+                    step.error = Some(message.to_string());
+                }
+            }
+            step.status = if cancelled && step.done < step.target {
+                step.error_class = Some(ErrorClass::Cancelled);
+                StepStatus::Cancelled
+            } else if step.target == 0 {
+                StepStatus::Skipped
+            } else if step.done >= step.target {
+                StepStatus::Completed
+            } else if result.is_err() || step.error_class.is_some() {
+                StepStatus::Failed
+            } else {
+                StepStatus::Partial
+            };
+            step.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            ended = Some((step.step.clone(), step.status, step.target, step.done));
+        });
+        self.log.set_step(None);
+
+        if let Some((step, status, target, done)) = ended {
+            let base = self.resumed_done(&step);
+            let status = match status {
+                StepStatus::Skipped if base > 0 => WorkStatus::Done,
+                status => status.into(),
+            };
+            self.update_queue(|run_id| {
+                queue::mark_step(&self.app, run_id, &self.address, &step, status, base + target, base + done)
+            });
+        }
+    }
+
+    pub(super) fn take_steps(&self) -> Vec<StepOutcome> {
+        self.end_step(Err("Step did not finish"));
+        std::mem::take(&mut *self.steps.lock().unwrap())
+    }
+
+    /// 记录执行前的积分
+    pub fn record_points_before(&self, points: u64) {
+        self.points.lock().unwrap().0 = Some(points);
+    }
+
+    /// 记录执行后的积分
+    pub fn record_points_after(&self, points: u64) {
+        self.points.lock().unwrap().1 = Some(points);
+    }
+
+    /// 等待一段时间，收到停止信号时提前返回
+    pub async fn sleep(&self, duration: Duration) {
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => {}
+            _ = self.cancel.cancelled() => {}
+        }
+    }
+
+    /// 步骤之间的随机延迟
+    pub async fn step_delay(&self) {
+        let delay = self.step_delay.sample(&mut *self.rng());
+        self.sleep(delay).await;
+    }
+
+    /// 今日已完成的次数；强制运行时只计本次运行（恢复前）已完成的
+    pub fn completed_today(&self, task: &str) -> u32 {
+        let resumed = self.resumed_done(task);
+        if self.force {
+            return resumed;
+        }
+        ledger::completed_count(&self.app, &self.script_id, &self.address, task, &self.ledger_day)
+            .unwrap_or_else(|e| {
+                self.log.warn(&format!("Failed to read task ledger: {}", e));
+                0
+            })
+            .max(resumed)
+    }
+
+    /// 恢复前本次运行已完成的次数
+    fn resumed_done(&self, step: &str) -> u32 {
+        self.resumed.get(step).copied().unwrap_or(0)
+    }
+
+    /// 今日还需执行的次数
+    pub fn remaining_today(&self, task: &str, target: u32) -> u32 {
+        target.saturating_sub(self.completed_today(task))
+    }
+
+    /// 记录一次任务完成
+    pub fn record_completed(&self, task: &str, tx_hash: Option<&str>, verified: Option<bool>) {
+        let entry = LedgerEntry {
+            script_id: self.script_id.clone(),
+            address: self.address.clone(),
+            task: task.to_string(),
+            day: self.ledger_day.clone(),
+            tx_hash: tx_hash.map(|s| s.to_string()),
+            verified,
+            completed_at: chrono::Utc::now().to_rfc3339(),
+        };
+        self.with_current_step(|step| {
+            if step.step == task {
+                step.done += 1;
+            }
+        });
+        if let Err(e) = ledger::record_completion(&self.app, entry) {
+            self.log.warn(&format!("Failed to write task ledger: {}", e));
+        } else if let Some(hash) = tx_hash {
+            self.update_queue(|run_id| {
+                queue::set_tx_state(&self.app, run_id, hash, TxState::Recorded)
+            });
+        }
+    }
+
+    /// 按打码设置为当前钱包打码，花费记在本次运行和钱包下
+    ///
+    /// 超出日预算或单次运行预算时停止整个运行，可被停止信号中断
+    pub async fn solve_captcha(&self, task: &CaptchaTask) -> Result<CaptchaSolution, String> {
+        let result = self
+            .run_until_cancelled(self.captcha.solve(&self.app, task, &self.address))
+            .await?;
+        result.map_err(|e| self.captcha_failed(e))
+    }
+
+    /// 从本次运行的预解池取令牌（适合多个钱包重复解同一验证码），停止信号可中断等待
+    pub async fn captcha_token(&self, task: &CaptchaTask) -> Result<CaptchaSolution, String> {
+        let result = self
+            .run_until_cancelled(self.captcha.pooled(&self.app, task))
+            .await?;
+        result.map_err(|e| self.captcha_failed(e))
+    }
+
+    /// 记录打码失败；预算耗尽时停止整个运行
+    fn captcha_failed(&self, e: CaptchaError) -> String {
+        if e.kind == CaptchaErrorKind::BudgetExceeded {
+            self.log.error(&format!("{}, stopping run", e));
+            self.cancel.cancel();
+        }
+        self.note_step_error(ErrorClass::Captcha, &e.to_string());
+        e.to_string()
+    }
+
+    /// 用钱包签名的 RPC provider，请求走钱包的 HTTP 客户端（同一代理、流量统计）
+    pub fn provider(&self, rpc_url: &str) -> DynProvider {
+        ProviderBuilder::new()
+            .wallet(EthereumWallet::from(self.signer.clone()))
+            .connect_client(create_rpc_client(&self.http, rpc_url))
+            .erased()
+    }
+}
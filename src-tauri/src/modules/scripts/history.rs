@@ -0,0 +1,433 @@
+use super::logs;
+use super::types::*;
+use crate::common::http::HttpError;
+use crate::common::recorder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+/// 失败原因分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    /// 连接、超时、代理等
+    Transport,
+    /// 非 2xx 响应
+    HttpStatus,
+    /// 响应无法解析
+    Decode,
+    /// 接口返回业务错误
+    Api,
+    /// 交易执行失败（回滚）
+    Reverted,
+    /// 等待回执失败
+    Receipt,
+    /// 打码失败或打码预算耗尽
+    Captcha,
+    Cancelled,
+    Other,
+}
+
+impl From<&HttpError> for ErrorClass {
+    fn from(e: &HttpError) -> Self {
+        match e {
+            HttpError::Transport(_) => Self::Transport,
+            HttpError::Status { .. } => Self::HttpStatus,
+            HttpError::Decode(_) => Self::Decode,
+            HttpError::Api { .. } => Self::Api,
+            HttpError::Replay(_) => Self::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Running,
+    /// 完成了目标次数
+    Completed,
+    /// 正常结束但未完成目标次数（如余额不足）
+    Partial,
+    /// 今日已完成，无需执行
+    Skipped,
+    Failed,
+    Cancelled,
+}
+
+/// 单个步骤的执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepOutcome {
+    pub step: String,
+    pub status: StepStatus,
+    /// 本次需要完成的次数（已扣除今日台账中的完成数）
+    pub target: u32,
+    /// 本次完成的次数
+    pub done: u32,
+    pub tx_hashes: Vec<String>,
+    pub error_class: Option<ErrorClass>,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+impl StepOutcome {
+    pub fn new(step: &str, target: u32) -> Self {
+        Self {
+            step: step.to_string(),
+            status: StepStatus::Running,
+            target,
+            done: 0,
+            tx_hashes: Vec::new(),
+            error_class: None,
+            error: None,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+        }
+    }
+}
+
+/// 单个钱包在一次运行中的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletRunRecord {
+    pub address: String,
+    pub success: bool,
+    pub cancelled: bool,
+    pub message: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub points_before: Option<u64>,
+    pub points_after: Option<u64>,
+    pub steps: Vec<StepOutcome>,
+    pub pending_txs: Vec<String>,
+    /// 脚本自定义的附加数据
+    #[serde(default)]
+    pub data: Option<Value>,
+}
+
+impl WalletRunRecord {
+    fn status(&self) -> &'static str {
+        if self.success {
+            "success"
+        } else if self.cancelled {
+            "cancelled"
+        } else {
+            "failed"
+        }
+    }
+
+    /// 落盘前脱敏：附加数据中的令牌（如登录 JWT）等字段和消息中的令牌
+    fn redact(&mut self) {
+        if let Some(data) = self.data.as_mut() {
+            recorder::redact_json(data);
+        }
+        self.message = logs::redact(&self.message, &[]);
+    }
+
+    pub fn result(&self) -> ScriptWalletResult {
+        ScriptWalletResult {
+            success: self.success,
+            data: self.data.clone(),
+            cancelled: self.cancelled,
+            pending_txs: self.pending_txs.clone(),
+            ..ScriptWalletResult::failed(self.message.clone())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    Running,
+    Completed,
+    Cancelled,
+    /// 应用在运行中退出，记录不完整
+    Interrupted,
+}
+
+/// 一次运行的完整记录，保存在 `script_runs/<run_id>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub run_id: String,
+    pub script_id: String,
+    pub config: Value,
+    pub options: RunOptions,
+    pub seed: u64,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub status: RunOutcome,
+    pub wallets: Vec<WalletRunRecord>,
+}
+
+impl RunRecord {
+    pub fn summary(&self) -> RunSummary {
+        let count = |s: &str| self.wallets.iter().filter(|w| w.status() == s).count();
+        RunSummary {
+            run_id: self.run_id.clone(),
+            script_id: self.script_id.clone(),
+            started_at: self.started_at.clone(),
+            finished_at: self.finished_at.clone(),
+            status: self.status,
+            total: self.wallets.len(),
+            succeeded: count("success"),
+            failed: count("failed"),
+            cancelled: count("cancelled"),
+        }
+    }
+
+    /// 各钱包的结果（键为地址）
+    pub fn results(&self) -> HashMap<String, ScriptWalletResult> {
+        self.wallets
+            .iter()
+            .map(|w| (w.address.clone(), w.result()))
+            .collect()
+    }
+
+    /// 加入或替换钱包记录
+    pub fn upsert_wallet(&mut self, record: WalletRunRecord) {
+        match self
+            .wallets
+            .iter_mut()
+            .find(|w| w.address.eq_ignore_ascii_case(&record.address))
+        {
+            Some(existing) => *existing = record,
+            None => self.wallets.push(record),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub run_id: String,
+    pub script_id: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub status: RunOutcome,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepDiff {
+    pub step: String,
+    pub base: Option<StepStatus>,
+    pub other: Option<StepStatus>,
+    pub base_done: u32,
+    pub other_done: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletDiff {
+    pub address: String,
+    /// success / failed / cancelled，None 表示该次运行未包含此钱包
+    pub base_status: Option<String>,
+    pub other_status: Option<String>,
+    pub base_points: Option<u64>,
+    pub other_points: Option<u64>,
+    /// 状态或完成次数有变化的步骤
+    pub steps: Vec<StepDiff>,
+}
+
+/// 两次运行的对比，只列出有变化的钱包
+#[derive(Debug, Clone, Serialize)]
+pub struct RunDiff {
+    pub base: RunSummary,
+    pub other: RunSummary,
+    pub config_changed: bool,
+    /// 失败变成功的钱包数
+    pub fixed: usize,
+    /// 成功变失败的钱包数
+    pub regressed: usize,
+    pub unchanged: usize,
+    pub wallets: Vec<WalletDiff>,
+}
+
+fn runs_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(app_data_dir.join("script_runs"))
+}
+
+/// 保存运行记录，钱包的附加数据脱敏后写入（内存中的记录不变）
+pub fn save_run_record(app: &tauri::AppHandle, record: &RunRecord) -> Result<(), String> {
+    let dir = runs_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let mut record = record.clone();
+    record.wallets.iter_mut().for_each(WalletRunRecord::redact);
+    let json = serde_json::to_string_pretty(&record).map_err(|e| e.to_string())?;
+    fs::write(dir.join(format!("{}.json", record.run_id)), json).map_err(|e| e.to_string())
+}
+
+pub fn load_run_record(app: &tauri::AppHandle, run_id: &str) -> Result<RunRecord, String> {
+    // 运行ID来自前端，拒绝路径字符
+    if run_id.is_empty() || !run_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid run id: {}", run_id));
+    }
+    let path = runs_dir(app)?.join(format!("{}.json", run_id));
+    if !path.exists() {
+        return Err(format!("Run not found: {}", run_id));
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| format!("Corrupted run record {}: {}", run_id, e))
+}
+
+/// 读取所有运行记录（损坏的文件跳过），按开始时间倒序
+pub fn load_run_records(app: &tauri::AppHandle) -> Result<Vec<RunRecord>, String> {
+    let dir = runs_dir(app)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut records: Vec<RunRecord> = fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    records.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(records)
+}
+
+pub fn diff_runs(base: &RunRecord, other: &RunRecord) -> RunDiff {
+    let by_address = |record: &RunRecord| -> HashMap<String, WalletRunRecord> {
+        record
+            .wallets
+            .iter()
+            .map(|w| (w.address.to_lowercase(), w.clone()))
+            .collect()
+    };
+    let base_wallets = by_address(base);
+    let other_wallets = by_address(other);
+    let addresses: BTreeSet<&String> = base_wallets.keys().chain(other_wallets.keys()).collect();
+
+    let (mut fixed, mut regressed, mut unchanged) = (0, 0, 0);
+    let mut wallets = Vec::new();
+
+    for address in addresses {
+        let b = base_wallets.get(address);
+        let o = other_wallets.get(address);
+
+        let step_names: BTreeSet<&String> = b
+            .into_iter()
+            .chain(o)
+            .flat_map(|w| w.steps.iter().map(|s| &s.step))
+            .collect();
+        let find = |w: Option<&WalletRunRecord>, name: &str| {
+            w.and_then(|w| w.steps.iter().find(|s| s.step == name))
+                .map(|s| (s.status, s.done))
+        };
+        let steps: Vec<StepDiff> = step_names
+            .into_iter()
+            .filter_map(|name| {
+                let (bs, os) = (find(b, name), find(o, name));
+                (bs != os).then(|| StepDiff {
+                    step: name.clone(),
+                    base: bs.map(|s| s.0),
+                    other: os.map(|s| s.0),
+                    base_done: bs.map_or(0, |s| s.1),
+                    other_done: os.map_or(0, |s| s.1),
+                })
+            })
+            .collect();
+
+        let base_status = b.map(|w| w.status());
+        let other_status = o.map(|w| w.status());
+        match (base_status, other_status) {
+            (Some(bs), Some("success")) if bs != "success" => fixed += 1,
+            (Some("success"), Some(os)) if os != "success" => regressed += 1,
+            _ => {}
+        }
+
+        let base_points = b.and_then(|w| w.points_after);
+        let other_points = o.and_then(|w| w.points_after);
+        if base_status == other_status && base_points == other_points && steps.is_empty() {
+            unchanged += 1;
+            continue;
+        }
+
+        wallets.push(WalletDiff {
+            address: b.or(o).map(|w| w.address.clone()).unwrap_or_default(),
+            base_status: base_status.map(|s| s.to_string()),
+            other_status: other_status.map(|s| s.to_string()),
+            base_points,
+            other_points,
+            steps,
+        });
+    }
+
+    RunDiff {
+        base: base.summary(),
+        other: other.summary(),
+        config_changed: base.config != other.config,
+        fixed,
+        regressed,
+        unchanged,
+        wallets,
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 每个钱包的每个步骤一行；没有步骤记录的钱包输出一行空步骤
+pub fn run_to_csv(record: &RunRecord) -> String {
+    let mut out = String::from(
+        "run_id,address,wallet_status,message,points_before,points_after,step,step_status,target,done,tx_hashes,error_class,error\n",
+    );
+    let opt = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_default();
+
+    for wallet in &record.wallets {
+        let prefix = [
+            record.run_id.clone(),
+            wallet.address.clone(),
+            wallet.status().to_string(),
+            logs::redact(&wallet.message, &[]),
+            opt(wallet.points_before),
+            opt(wallet.points_after),
+        ];
+
+        let rows: Vec<[String; 7]> = if wallet.steps.is_empty() {
+            vec![Default::default()]
+        } else {
+            wallet
+                .steps
+                .iter()
+                .map(|s| {
+                    [
+                        s.step.clone(),
+                        serde_json::to_value(s.status)
+                            .ok()
+                            .and_then(|v| v.as_str().map(|s| s.to_string()))
+                            .unwrap_or_default(),
+                        s.target.to_string(),
+                        s.done.to_string(),
+                        s.tx_hashes.join(" "),
+                        s.error_class
+                            .and_then(|c| serde_json::to_value(c).ok())
+                            .and_then(|v| v.as_str().map(|s| s.to_string()))
+                            .unwrap_or_default(),
+                        s.error.clone().unwrap_or_default(),
+                    ]
+                })
+                .collect()
+        };
+
+        for row in rows {
+            let line: Vec<String> = prefix.iter().chain(row.iter()).map(|f| csv_field(f)).collect();
+            out.push_str(&line.join(","));
+            out.push('\n');
+        }
+    }
+    out
+}
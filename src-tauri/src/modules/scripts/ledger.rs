@@ -0,0 +1,158 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+use tauri::Manager;
+
+/// 台账保留天数，更早的记录在写入时清理
+const LEDGER_KEEP_DAYS: i64 = 7;
+
+lazy_static! {
+    /// 台账内存副本，首次使用时从磁盘加载，每次记录后写回
+    static ref LEDGER: Mutex<Option<Vec<LedgerEntry>>> = Mutex::new(None);
+}
+
+/// 每日任务完成记录（一次完成一条）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub script_id: String,
+    /// 小写地址
+    pub address: String,
+    pub task: String,
+    /// 项目日（按重置时间划分的 UTC 日期，YYYY-MM-DD）
+    pub day: String,
+    pub tx_hash: Option<String>,
+    /// 项目方验证结果，None 表示未验证（如无 JWT 或不需要验证）
+    pub verified: Option<bool>,
+    pub completed_at: String,
+}
+
+impl LedgerEntry {
+    /// 验证失败的记录不计入当日完成数，下次运行会重做
+    fn counts(&self) -> bool {
+        self.verified != Some(false)
+    }
+}
+
+/// 当前的项目日，`reset_minutes` 为每日重置时间（UTC 零点后的分钟数）
+pub fn ledger_day(reset_minutes: u32) -> String {
+    ledger_day_at(chrono::Utc::now(), reset_minutes)
+}
+
+/// 指定时间所属的项目日
+pub fn ledger_day_at(at: chrono::DateTime<chrono::Utc>, reset_minutes: u32) -> String {
+    (at - chrono::Duration::minutes(reset_minutes as i64))
+        .date_naive()
+        .to_string()
+}
+
+fn load_ledger_file(app: &tauri::AppHandle) -> Result<Vec<LedgerEntry>, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let ledger_path = app_data_dir.join("task_ledger.json");
+
+    if !ledger_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&ledger_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+fn save_ledger_file(app: &tauri::AppHandle, entries: &[LedgerEntry]) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&app_data_dir).map_err(|e| e.to_string())?;
+
+    let json = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    fs::write(app_data_dir.join("task_ledger.json"), json).map_err(|e| e.to_string())
+}
+
+fn with_ledger<R>(
+    app: &tauri::AppHandle,
+    f: impl FnOnce(&mut Vec<LedgerEntry>) -> R,
+) -> Result<R, String> {
+    let mut ledger = LEDGER.lock().unwrap();
+    if ledger.is_none() {
+        *ledger = Some(load_ledger_file(app)?);
+    }
+    Ok(f(ledger.as_mut().unwrap()))
+}
+
+/// 某钱包某任务在指定项目日的完成次数
+pub fn completed_count(
+    app: &tauri::AppHandle,
+    script_id: &str,
+    address: &str,
+    task: &str,
+    day: &str,
+) -> Result<u32, String> {
+    let address = address.to_lowercase();
+    with_ledger(app, |entries| {
+        entries
+            .iter()
+            .filter(|e| {
+                e.script_id == script_id
+                    && e.address == address
+                    && e.task == task
+                    && e.day == day
+                    && e.counts()
+            })
+            .count() as u32
+    })
+}
+
+/// 记录一次完成并立即落盘，重启后不会重复执行
+pub fn record_completion(app: &tauri::AppHandle, mut entry: LedgerEntry) -> Result<(), String> {
+    entry.address = entry.address.to_lowercase();
+    let oldest = (chrono::Utc::now() - chrono::Duration::days(LEDGER_KEEP_DAYS))
+        .date_naive()
+        .to_string();
+
+    with_ledger(app, |entries| {
+        entries.retain(|e| e.day >= oldest);
+        entries.push(entry);
+        save_ledger_file(app, entries)
+    })?
+}
+
+/// 查询台账，地址和项目日为空时不过滤
+#[tauri::command]
+pub fn get_task_ledger(
+    app: tauri::AppHandle,
+    script_id: String,
+    address: Option<String>,
+    day: Option<String>,
+) -> Result<Vec<LedgerEntry>, String> {
+    let address = address.map(|a| a.to_lowercase());
+    with_ledger(&app, |entries| {
+        entries
+            .iter()
+            .filter(|e| e.script_id == script_id)
+            .filter(|e| address.as_ref().is_none_or(|a| &e.address == a))
+            .filter(|e| day.as_ref().is_none_or(|d| &e.day == d))
+            .cloned()
+            .collect()
+    })
+}
+
+/// 清除台账记录（可限定地址），返回删除的条数
+#[tauri::command]
+pub fn clear_task_ledger(
+    app: tauri::AppHandle,
+    script_id: String,
+    address: Option<String>,
+) -> Result<usize, String> {
+    let address = address.map(|a| a.to_lowercase());
+
+    with_ledger(&app, |entries| {
+        let before = entries.len();
+        entries.retain(|e| {
+            e.script_id != script_id || address.as_ref().is_some_and(|a| &e.address != a)
+        });
+        let removed = before - entries.len();
+
+        if removed > 0 {
+            save_ledger_file(&app, entries)?;
+        }
+        Ok(removed)
+    })?
+}
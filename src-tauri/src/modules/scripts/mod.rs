@@ -1 +1,23 @@
+pub mod types;
+pub mod context;
+pub mod registry;
+pub mod runner;
+pub mod presets;
+pub mod ledger;
+pub mod history;
+pub mod logs;
+pub mod scheduler;
+pub mod queue;
+pub mod captcha;
+pub mod commands;
 pub mod pharos;
+
+pub use types::*;
+pub use context::*;
+pub use presets::*;
+pub use ledger::*;
+pub use history::*;
+pub use logs::*;
+pub use scheduler::*;
+pub use queue::*;
+pub use commands::*;
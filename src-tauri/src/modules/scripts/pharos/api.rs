@@ -1,22 +1,29 @@
-use reqwest::Client;
-use crate::common::http::create_client;
+use crate::common::http::{ApiResponse, HttpClient, HttpError};
+use crate::common::rate_limit;
 use super::types::*;
 use reqwest::header::HeaderMap;
 
+/// Pharos API 域名（启用 impersonate 时模拟浏览器握手）
+pub const PHAROS_API_HOST: &str = "api.pharosnetwork.xyz";
+
+/// Pharos API 默认限速（所有钱包共享，可在设置中覆盖）
+const PHAROS_API_RATE: f64 = 5.0;
+const PHAROS_API_BURST: u32 = 10;
+
 pub struct PharosClient {
-    client: Client,
+    client: HttpClient,
     address: String,
     jwt: Option<String>,
 }
 
 impl PharosClient {
-    pub fn new(address: &str, jwt: Option<String>) -> Result<Self, String> {
-        let client = create_client(None)?;
-        Ok(Self {
+    pub fn new(address: &str, jwt: Option<String>, client: HttpClient) -> Self {
+        rate_limit::ensure_limit(PHAROS_API_HOST, PHAROS_API_RATE, PHAROS_API_BURST);
+        Self {
             client,
             address: address.to_string(),
             jwt,
-        })
+        }
     }
     
     fn headers(&self) -> HeaderMap {
@@ -28,71 +35,63 @@ impl PharosClient {
         };
         
         headers.insert("authorization", auth_val.parse().unwrap());
-        headers.insert("sec-ch-ua", "\"Chromium\";v=\"136\", \"Brave\";v=\"136\", \"Not.A/Brand\";v=\"99\"".parse().unwrap());
-        headers.insert("sec-ch-ua-mobile", "?0".parse().unwrap());
-        headers.insert("sec-ch-ua-platform", "\"Windows\"".parse().unwrap());
+        // User-Agent / sec-ch-ua 由钱包指纹写入客户端默认头
         headers.insert("sec-fetch-dest", "empty".parse().unwrap());
         headers.insert("sec-fetch-mode", "cors".parse().unwrap());
         headers.insert("sec-fetch-site", "same-site".parse().unwrap());
-        headers.insert("sec-gpc", "1".parse().unwrap());
         headers.insert("Referer", "https://testnet.pharosnetwork.xyz/".parse().unwrap());
         headers.insert("Referrer-Policy", "strict-origin-when-cross-origin".parse().unwrap());
         
         headers
     }
 
-    async fn get<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, String> {
-        let response = self.client.get(url)
-            .headers(self.headers())
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?;
+    async fn get<T: serde::de::DeserializeOwned + ApiResponse>(&self, url: &str) -> Result<T, HttpError> {
+        let request = self.client.get(url).headers(self.headers());
+        let response = self.client.send(request).await?;
             
-        let text = response.text().await
-            .map_err(|e| format!("Failed to read response: {}", e))?;
-            
-        serde_json::from_str::<T>(&text)
-            .map_err(|e| format!("Failed to parse response: {} - Raw: {}", e, text))
+        response.api_json::<T>()
     }
 
-    async fn post<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, String> {
-        let response = self.client.post(url)
-            .headers(self.headers())
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?;
-            
-        let text = response.text().await
-            .map_err(|e| format!("Failed to read response: {}", e))?;
+    async fn post<T: serde::de::DeserializeOwned + ApiResponse>(&self, url: &str) -> Result<T, HttpError> {
+        let request = self.client.post(url).headers(self.headers());
+        let response = self.client.send(request).await?;
             
-        serde_json::from_str::<T>(&text)
-            .map_err(|e| format!("Failed to parse response: {} - Raw: {}", e, text))
+        response.api_json::<T>()
     }
 
-    pub async fn login(&self, signature: &str, invite_code: &str) -> Result<PharosLoginResponse, String> {
+    /// 可安全重发的 POST，429/5xx 和网络错误时重试
+    async fn post_idempotent<T: serde::de::DeserializeOwned + ApiResponse>(&self, url: &str) -> Result<T, HttpError> {
+        let request = self.client.post(url).headers(self.headers());
+        let response = self.client.send_idempotent(request).await?;
+
+        response.api_json::<T>()
+    }
+
+    pub async fn login(&self, signature: &str, invite_code: &str) -> Result<PharosLoginResponse, HttpError> {
         let url = format!(
             "https://api.pharosnetwork.xyz/user/login?address={}&signature={}&invite_code={}",
             self.address, signature, invite_code
         );
-        self.post(&url).await
+        // 重复登录只会拿到新的 JWT，失败时可按幂等请求重试
+        self.post_idempotent(&url).await
     }
     
-    pub async fn check_in(&self) -> Result<PharosBaseResponse, String> {
+    pub async fn check_in(&self) -> Result<PharosBaseResponse, HttpError> {
         let url = format!("https://api.pharosnetwork.xyz/sign/in?address={}", self.address);
         self.post(&url).await
     }
     
-    pub async fn get_faucet_status(&self) -> Result<PharosFaucetStatusResponse, String> {
+    pub async fn get_faucet_status(&self) -> Result<PharosFaucetStatusResponse, HttpError> {
         let url = format!("https://api.pharosnetwork.xyz/faucet/status?address={}", self.address);
         self.get(&url).await
     }
     
-    pub async fn claim_faucet(&self) -> Result<PharosBaseResponse, String> {
+    pub async fn claim_faucet(&self) -> Result<PharosBaseResponse, HttpError> {
         let url = format!("https://api.pharosnetwork.xyz/faucet/daily?address={}", self.address);
         self.post(&url).await
     }
     
-    pub async fn get_profile(&self) -> Result<PharosProfileResponse, String> {
+    pub async fn get_profile(&self) -> Result<PharosProfileResponse, HttpError> {
         let url = format!("https://api.pharosnetwork.xyz/user/profile?address={}", self.address);
         self.get(&url).await
     }
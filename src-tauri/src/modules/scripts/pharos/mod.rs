@@ -1,5 +1,8 @@
 pub mod types;
-pub mod commands;
 pub mod api;
+pub mod tasks;
+pub mod script;
+pub mod commands;
 
+pub use script::*;
 pub use commands::*;
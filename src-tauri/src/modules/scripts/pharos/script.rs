@@ -0,0 +1,243 @@
+use super::api::PHAROS_API_HOST;
+use super::tasks::{run_wallet_tasks, PHAROS_RPC_URL};
+use super::types::*;
+use crate::modules::scripts::{
+    AmountRange, Cancelled, Script, ScriptContext, ScriptFuture, ScriptMeta, ScriptWalletResult,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub const PHAROS_SCRIPT_ID: &str = "pharos";
+const DEFAULT_INVITE_CODE: &str = "S6NGMzXSCDBxhnwo";
+
+/// 可计次的链上步骤配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PharosStepConfig {
+    pub enabled: bool,
+    /// 执行次数
+    pub count: u32,
+    /// 每次金额范围（转账/wrap/unwrap 为 PHRS，swap/加流动性为源代币数量）
+    pub amount: AmountRange,
+}
+
+impl PharosStepConfig {
+    const fn new(enabled: bool, count: u32, min: f64, max: f64) -> Self {
+        Self {
+            enabled,
+            count,
+            amount: AmountRange::new(min, max),
+        }
+    }
+}
+
+/// Pharos 脚本配置：登录和查询资料总会执行，其余步骤按开关和次数执行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PharosConfig {
+    pub invite_code: String,
+    pub check_in: bool,
+    pub faucet: bool,
+    pub transfer: PharosStepConfig,
+    pub wrap: PharosStepConfig,
+    pub unwrap: PharosStepConfig,
+    pub swap: PharosStepConfig,
+    pub add_liquidity: PharosStepConfig,
+    /// PHRS 余额低于此值时跳过所有链上步骤
+    pub min_balance: f64,
+    /// 每日任务重置时间（UTC 小时），今日已完成的步骤在重置前不再执行
+    pub reset_hour_utc: u32,
+}
+
+impl Default for PharosConfig {
+    fn default() -> Self {
+        Self {
+            invite_code: DEFAULT_INVITE_CODE.to_string(),
+            check_in: true,
+            faucet: true,
+            transfer: PharosStepConfig::new(true, 10, 0.000001, 0.00001),
+            wrap: PharosStepConfig::new(true, 10, 0.001, 0.005),
+            unwrap: PharosStepConfig::new(false, 5, 0.001, 0.003),
+            swap: PharosStepConfig::new(false, 10, 0.01, 0.01),
+            add_liquidity: PharosStepConfig::new(false, 10, 0.0001, 0.0001),
+            min_balance: 0.001,
+            reset_hour_utc: 0,
+        }
+    }
+}
+
+impl PharosConfig {
+    /// 是否有需要上链的步骤
+    pub fn has_chain_steps(&self) -> bool {
+        [&self.transfer, &self.wrap, &self.unwrap, &self.swap, &self.add_liquidity]
+            .iter()
+            .any(|s| s.enabled && s.count > 0)
+    }
+}
+
+impl From<PharosTaskResult> for ScriptWalletResult {
+    fn from(r: PharosTaskResult) -> Self {
+        Self {
+            success: r.success,
+            data: r.jwt.map(|jwt| json!({ "jwt": jwt })),
+            ..ScriptWalletResult::failed(r.message)
+        }
+    }
+}
+
+impl From<Cancelled> for PharosTaskResult {
+    fn from(e: Cancelled) -> Self {
+        Self {
+            success: false,
+            message: e.to_string(),
+            jwt: None,
+        }
+    }
+}
+
+impl From<ScriptWalletResult> for PharosTaskResult {
+    fn from(r: ScriptWalletResult) -> Self {
+        Self {
+            success: r.success,
+            message: r.message,
+            jwt: r
+                .data
+                .as_ref()
+                .and_then(|d| d.get("jwt"))
+                .and_then(|j| j.as_str())
+                .map(|s| s.to_string()),
+        }
+    }
+}
+
+fn step_schema(title: &str, step: &PharosStepConfig) -> Value {
+    json!({
+        "type": "object",
+        "title": title,
+        "properties": {
+            "enabled": { "type": "boolean", "title": "启用", "default": step.enabled },
+            "count": { "type": "integer", "title": "次数", "minimum": 0, "default": step.count },
+            "amount": {
+                "type": "object",
+                "title": "金额范围",
+                "properties": {
+                    "min": { "type": "number", "default": step.amount.min },
+                    "max": { "type": "number", "default": step.amount.max }
+                }
+            }
+        }
+    })
+}
+
+pub struct PharosScript;
+
+impl Script for PharosScript {
+    fn meta(&self) -> ScriptMeta {
+        let defaults = PharosConfig::default();
+        ScriptMeta {
+            id: PHAROS_SCRIPT_ID.to_string(),
+            name: "Pharos Testnet".to_string(),
+            description: "自动化执行 Pharos 测试网任务".to_string(),
+            logo: "💡".to_string(),
+            category: "自动化脚本".to_string(),
+            website: "https://pharos.xyz".to_string(),
+            config_schema: json!({
+                "type": "object",
+                "properties": {
+                    "invite_code": {
+                        "type": "string",
+                        "title": "邀请码",
+                        "default": defaults.invite_code
+                    },
+                    "check_in": { "type": "boolean", "title": "签到", "default": defaults.check_in },
+                    "faucet": { "type": "boolean", "title": "领水", "default": defaults.faucet },
+                    "transfer": step_schema("转账 (PHRS)", &defaults.transfer),
+                    "wrap": step_schema("Wrap PHRS", &defaults.wrap),
+                    "unwrap": step_schema("Unwrap WPHRS", &defaults.unwrap),
+                    "swap": step_schema("Swap（暂不可用）", &defaults.swap),
+                    "add_liquidity": step_schema("添加流动性（暂不可用）", &defaults.add_liquidity),
+                    "min_balance": {
+                        "type": "number",
+                        "title": "最低 PHRS 余额",
+                        "default": defaults.min_balance
+                    },
+                    "reset_hour_utc": {
+                        "type": "integer",
+                        "title": "每日重置时间 (UTC 小时)",
+                        "minimum": 0,
+                        "maximum": 23,
+                        "default": defaults.reset_hour_utc
+                    }
+                }
+            }),
+        }
+    }
+
+    fn impersonate_hosts(&self) -> &'static [&'static str] {
+        &[PHAROS_API_HOST]
+    }
+
+    fn legacy_log_event(&self) -> Option<&'static str> {
+        Some("pharos_log")
+    }
+
+    fn daily_reset_minutes(&self, config: &Value) -> u32 {
+        serde_json::from_value::<PharosConfig>(config.clone())
+            .map(|c| c.reset_hour_utc * 60)
+            .unwrap_or(0)
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<(), String> {
+        let config = serde_json::from_value::<PharosConfig>(config.clone())
+            .map_err(|e| format!("Invalid Pharos config: {}", e))?;
+        if config.reset_hour_utc > 23 {
+            return Err("reset_hour_utc must be between 0 and 23".to_string());
+        }
+        // swap 和 add_liquidity 使用的池子（DLP）地址仍是占位地址，启用会把资金打到未知合约
+        for (name, step) in [("swap", &config.swap), ("add_liquidity", &config.add_liquidity)] {
+            if step.enabled {
+                return Err(format!(
+                    "{} is not available: pool addresses are not configured yet",
+                    name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn steps(&self, config: &Value) -> Vec<String> {
+        let config = serde_json::from_value::<PharosConfig>(config.clone()).unwrap_or_default();
+        let mut steps = vec!["login"];
+        if config.check_in {
+            steps.push("check_in");
+        }
+        if config.faucet {
+            steps.push("faucet");
+        }
+        for (name, step) in [
+            ("transfer", &config.transfer),
+            ("wrap", &config.wrap),
+            ("unwrap", &config.unwrap),
+            ("swap", &config.swap),
+            ("add_liquidity", &config.add_liquidity),
+        ] {
+            if step.enabled && step.count > 0 {
+                steps.push(name);
+            }
+        }
+        steps.into_iter().map(String::from).collect()
+    }
+
+    fn rpc_url(&self, _config: &Value) -> Option<&'static str> {
+        Some(PHAROS_RPC_URL)
+    }
+
+    fn run<'a>(&'a self, ctx: &'a ScriptContext) -> ScriptFuture<'a, ScriptWalletResult> {
+        Box::pin(async move {
+            let config: PharosConfig = match ctx.config() {
+                Ok(c) => c,
+                Err(e) => return ScriptWalletResult::failed(e),
+            };
+            run_wallet_tasks(ctx, &config).await.into()
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use crate::common::http::ApiResponse;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -51,6 +52,7 @@ pub struct PharosTaskResult {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PharosStatusResponse {
     pub is_running: bool,
+    pub run_id: Option<String>,
     pub results: HashMap<String, PharosTaskResult>,
 }
 
@@ -66,3 +68,26 @@ pub struct PharosFaucetStatusResponse {
 pub struct PharosFaucetStatusData {
     pub is_able_to_faucet: bool,
 }
+
+macro_rules! impl_api_response {
+    ($($t:ty),*) => {
+        $(
+            impl ApiResponse for $t {
+                fn api_code(&self) -> i64 {
+                    self.code as i64
+                }
+
+                fn api_message(&self) -> String {
+                    self.msg.clone()
+                }
+            }
+        )*
+    };
+}
+
+impl_api_response!(
+    PharosLoginResponse,
+    PharosBaseResponse,
+    PharosProfileResponse,
+    PharosFaucetStatusResponse
+);
@@ -0,0 +1,112 @@
+use super::registry;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use tauri::Manager;
+
+/// 命名的脚本配置预设
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptPreset {
+    pub name: String,
+    pub config: Value,
+    pub updated_at: String,
+}
+
+/// 读取所有预设（键为脚本ID）
+pub fn load_script_presets(
+    app: &tauri::AppHandle,
+) -> Result<HashMap<String, Vec<ScriptPreset>>, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let presets_path = app_data_dir.join("script_presets.json");
+
+    if !presets_path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content = fs::read_to_string(&presets_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+fn save_script_presets(
+    app: &tauri::AppHandle,
+    presets: &HashMap<String, Vec<ScriptPreset>>,
+) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&app_data_dir).map_err(|e| e.to_string())?;
+
+    let json = serde_json::to_string_pretty(presets).map_err(|e| e.to_string())?;
+    fs::write(app_data_dir.join("script_presets.json"), json).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// 按名称取预设配置
+pub fn preset_config(app: &tauri::AppHandle, script_id: &str, name: &str) -> Result<Value, String> {
+    load_script_presets(app)?
+        .remove(script_id)
+        .unwrap_or_default()
+        .into_iter()
+        .find(|p| p.name == name)
+        .map(|p| p.config)
+        .ok_or_else(|| format!("Preset not found: {}", name))
+}
+
+#[tauri::command]
+pub fn list_script_presets(
+    app: tauri::AppHandle,
+    script_id: String,
+) -> Result<Vec<ScriptPreset>, String> {
+    Ok(load_script_presets(&app)?
+        .remove(&script_id)
+        .unwrap_or_default())
+}
+
+/// 保存预设，同名覆盖
+#[tauri::command]
+pub fn save_script_preset(
+    app: tauri::AppHandle,
+    script_id: String,
+    name: String,
+    config: Value,
+) -> Result<ScriptPreset, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Preset name is required".to_string());
+    }
+    registry::get_script(&script_id)?.validate_config(&config)?;
+
+    let preset = ScriptPreset {
+        name: name.clone(),
+        config,
+        updated_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let mut presets = load_script_presets(&app)?;
+    let list = presets.entry(script_id).or_default();
+    match list.iter_mut().find(|p| p.name == name) {
+        Some(existing) => *existing = preset.clone(),
+        None => list.push(preset.clone()),
+    }
+    save_script_presets(&app, &presets)?;
+
+    Ok(preset)
+}
+
+#[tauri::command]
+pub fn delete_script_preset(
+    app: tauri::AppHandle,
+    script_id: String,
+    name: String,
+) -> Result<bool, String> {
+    let mut presets = load_script_presets(&app)?;
+    let list = presets.entry(script_id).or_default();
+    let before = list.len();
+    list.retain(|p| p.name != name);
+    let removed = list.len() != before;
+
+    if removed {
+        save_script_presets(&app, &presets)?;
+    }
+    Ok(removed)
+}
@@ -0,0 +1,541 @@
+use super::context::ScriptLogger;
+use super::history::{load_run_record, save_run_record, RunOutcome, RunRecord, RunSummary, StepStatus};
+use super::ledger::{self, LedgerEntry};
+use super::runner;
+use super::types::*;
+use crate::common::rpc::create_rpc_client;
+use crate::modules::proxy::create_wallet_client;
+use alloy::primitives::TxHash;
+use alloy::providers::{Provider, ProviderBuilder};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+lazy_static! {
+    /// 进行中运行的队列，每次修改后写盘
+    static ref QUEUES: Mutex<HashMap<String, WorkQueue>> = Mutex::new(HashMap::new());
+}
+
+/// 队列项（钱包 × 步骤）状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkStatus {
+    Pending,
+    Running,
+    Done,
+    Partial,
+    Skipped,
+    Failed,
+    Cancelled,
+}
+
+impl From<StepStatus> for WorkStatus {
+    fn from(status: StepStatus) -> Self {
+        match status {
+            StepStatus::Running => WorkStatus::Running,
+            StepStatus::Completed => WorkStatus::Done,
+            StepStatus::Partial => WorkStatus::Partial,
+            StepStatus::Skipped => WorkStatus::Skipped,
+            StepStatus::Failed => WorkStatus::Failed,
+            StepStatus::Cancelled => WorkStatus::Cancelled,
+        }
+    }
+}
+
+/// 已广播交易的核对状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxState {
+    /// 已广播，未收到回执
+    Pending,
+    /// 回执成功，尚未写入任务台账
+    Confirmed,
+    Reverted,
+    /// 链上和交易池都查不到
+    Dropped,
+    /// 已写入任务台账
+    Recorded,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTx {
+    pub hash: String,
+    pub state: TxState,
+    pub sent_at: String,
+    /// 辅助交易（如代币授权），不代表步骤完成，核对时不写台账
+    #[serde(default)]
+    pub auxiliary: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkItem {
+    /// 小写地址
+    pub address: String,
+    pub step: String,
+    pub status: WorkStatus,
+    pub target: u32,
+    pub done: u32,
+    #[serde(default)]
+    pub txs: Vec<QueuedTx>,
+    pub updated_at: Option<String>,
+}
+
+impl WorkItem {
+    fn new(address: &str, step: &str) -> Self {
+        Self {
+            address: address.to_lowercase(),
+            step: step.to_string(),
+            status: WorkStatus::Pending,
+            target: 0,
+            done: 0,
+            txs: Vec::new(),
+            updated_at: None,
+        }
+    }
+}
+
+/// 一次运行的工作队列，保存在 `script_queue/<run_id>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkQueue {
+    pub run_id: String,
+    pub script_id: String,
+    /// 运行正常结束（完成或停止）后置为 true，否则视为中断
+    pub finished: bool,
+    pub items: Vec<WorkItem>,
+    pub updated_at: String,
+}
+
+impl WorkQueue {
+    fn item_mut(&mut self, address: &str, step: &str) -> &mut WorkItem {
+        let address = address.to_lowercase();
+        let index = match self
+            .items
+            .iter()
+            .position(|i| i.address == address && i.step == step)
+        {
+            Some(index) => index,
+            None => {
+                self.items.push(WorkItem::new(&address, step));
+                self.items.len() - 1
+            }
+        };
+        &mut self.items[index]
+    }
+
+    fn tx_mut(&mut self, hash: &str) -> Option<&mut QueuedTx> {
+        self.items
+            .iter_mut()
+            .flat_map(|i| i.txs.iter_mut())
+            .find(|t| t.hash.eq_ignore_ascii_case(hash))
+    }
+}
+
+/// 各钱包（小写地址）各步骤在队列中已完成的次数
+pub type CompletedSteps = HashMap<String, HashMap<String, u32>>;
+
+/// 队列中已完成的步骤次数，取步骤记录的完成数和已写台账的交易数中较大的
+///
+/// 崩溃时步骤尚未结束，完成数未落盘，但核对后写入台账的交易已标为 `Recorded`
+fn completed_steps(queue: &WorkQueue) -> CompletedSteps {
+    let mut completed = CompletedSteps::new();
+    for item in &queue.items {
+        let recorded = item
+            .txs
+            .iter()
+            .filter(|t| !t.auxiliary && t.state == TxState::Recorded)
+            .count() as u32;
+        let done = item.done.max(recorded);
+        if done > 0 {
+            completed
+                .entry(item.address.clone())
+                .or_default()
+                .insert(item.step.clone(), done);
+        }
+    }
+    completed
+}
+
+/// 中断运行的核对结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconcileReport {
+    pub confirmed: usize,
+    pub reverted: usize,
+    pub dropped: usize,
+    /// 仍在交易池中的交易，确认前不能恢复
+    pub still_pending: Vec<String>,
+}
+
+/// 可恢复的中断运行
+#[derive(Debug, Clone, Serialize)]
+pub struct InterruptedRun {
+    pub summary: RunSummary,
+    /// 未完成的队列项数
+    pub remaining_items: usize,
+    /// 中断时未确认的交易数
+    pub pending_txs: usize,
+}
+
+fn queue_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(app_data_dir.join("script_queue"))
+}
+
+fn queue_path(app: &tauri::AppHandle, run_id: &str) -> Result<PathBuf, String> {
+    if run_id.is_empty() || !run_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid run id: {}", run_id));
+    }
+    Ok(queue_dir(app)?.join(format!("{}.json", run_id)))
+}
+
+/// 先写临时文件再改名，崩溃时不会留下半个文件
+fn save_queue(app: &tauri::AppHandle, queue: &WorkQueue) -> Result<(), String> {
+    let path = queue_path(app, &queue.run_id)?;
+    fs::create_dir_all(queue_dir(app)?).map_err(|e| e.to_string())?;
+
+    let json = serde_json::to_string_pretty(queue).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+pub fn load_queue(app: &tauri::AppHandle, run_id: &str) -> Result<WorkQueue, String> {
+    if let Some(queue) = QUEUES.lock().unwrap().get(run_id) {
+        return Ok(queue.clone());
+    }
+    let content = fs::read_to_string(queue_path(app, run_id)?)
+        .map_err(|_| format!("Work queue not found: {}", run_id))?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+fn with_queue<R>(
+    app: &tauri::AppHandle,
+    run_id: &str,
+    f: impl FnOnce(&mut WorkQueue) -> R,
+) -> Result<R, String> {
+    let mut queues = QUEUES.lock().unwrap();
+    if !queues.contains_key(run_id) {
+        let content = fs::read_to_string(queue_path(app, run_id)?)
+            .map_err(|_| format!("Work queue not found: {}", run_id))?;
+        let queue = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        queues.insert(run_id.to_string(), queue);
+    }
+
+    let queue = queues.get_mut(run_id).unwrap();
+    let result = f(queue);
+    queue.updated_at = chrono::Utc::now().to_rfc3339();
+    save_queue(app, queue)?;
+    Ok(result)
+}
+
+/// 建立（或恢复时补全）运行的队列，每个钱包的每个计划步骤一项
+pub fn open_queue(
+    app: &tauri::AppHandle,
+    run_id: &str,
+    script_id: &str,
+    addresses: &[String],
+    steps: &[String],
+) -> Result<(), String> {
+    let mut queue = load_queue(app, run_id).unwrap_or_else(|_| WorkQueue {
+        run_id: run_id.to_string(),
+        script_id: script_id.to_string(),
+        finished: false,
+        items: Vec::new(),
+        updated_at: String::new(),
+    });
+    queue.finished = false;
+    for address in addresses {
+        for step in steps {
+            queue.item_mut(address, step);
+        }
+    }
+    queue.updated_at = chrono::Utc::now().to_rfc3339();
+
+    save_queue(app, &queue)?;
+    QUEUES.lock().unwrap().insert(run_id.to_string(), queue);
+    Ok(())
+}
+
+/// 运行正常结束，队列不再作为中断运行提供恢复
+pub fn close_queue(app: &tauri::AppHandle, run_id: &str) -> Result<(), String> {
+    let result = with_queue(app, run_id, |queue| queue.finished = true);
+    QUEUES.lock().unwrap().remove(run_id);
+    result
+}
+
+/// 更新步骤状态
+pub fn mark_step(
+    app: &tauri::AppHandle,
+    run_id: &str,
+    address: &str,
+    step: &str,
+    status: WorkStatus,
+    target: u32,
+    done: u32,
+) -> Result<(), String> {
+    with_queue(app, run_id, |queue| {
+        let item = queue.item_mut(address, step);
+        item.status = status;
+        item.target = target;
+        item.done = done;
+        item.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    })
+}
+
+/// 交易广播后立即落盘，崩溃后据此到链上核对
+pub fn add_tx(
+    app: &tauri::AppHandle,
+    run_id: &str,
+    address: &str,
+    step: &str,
+    hash: &str,
+    auxiliary: bool,
+) -> Result<(), String> {
+    with_queue(app, run_id, |queue| {
+        queue.item_mut(address, step).txs.push(QueuedTx {
+            hash: hash.to_string(),
+            state: TxState::Pending,
+            sent_at: chrono::Utc::now().to_rfc3339(),
+            auxiliary,
+        });
+    })
+}
+
+pub fn set_tx_state(
+    app: &tauri::AppHandle,
+    run_id: &str,
+    hash: &str,
+    state: TxState,
+) -> Result<(), String> {
+    with_queue(app, run_id, |queue| {
+        if let Some(tx) = queue.tx_mut(hash) {
+            tx.state = state;
+        }
+    })
+}
+
+/// 到链上核对中断时未确认的交易
+///
+/// 成功的交易按广播时的项目日写入任务台账（未向项目方验证），恢复后对应步骤不会重做；
+/// 仍在交易池中的交易返回在 `still_pending` 中。辅助交易（授权等）不计入步骤完成，跳过
+async fn reconcile_queue(
+    app: &tauri::AppHandle,
+    script: &dyn Script,
+    record: &RunRecord,
+) -> Result<ReconcileReport, String> {
+    let queue = load_queue(app, &record.run_id)?;
+    let reset_minutes = script.daily_reset_minutes(&record.config);
+    let log = ScriptLogger::new(app, script, "SYSTEM").with_run(Some(&record.run_id));
+
+    let mut report = ReconcileReport::default();
+    for item in &queue.items {
+        for tx in item.txs.iter().filter(|t| !t.auxiliary) {
+            let state = match tx.state {
+                TxState::Pending => {
+                    let rpc_url = script
+                        .rpc_url(&record.config)
+                        .ok_or("Script has no RPC to check pending transactions")?;
+                    let hash: TxHash = tx
+                        .hash
+                        .parse()
+                        .map_err(|e| format!("Invalid tx hash {}: {}", tx.hash, e))?;
+                    let http = create_wallet_client(app, &item.address, Some(&record.run_id), None)?;
+                    let provider = ProviderBuilder::new()
+                        .connect_client(create_rpc_client(&http, rpc_url))
+                        .erased();
+
+                    match provider
+                        .get_transaction_receipt(hash)
+                        .await
+                        .map_err(|e| format!("Failed to check tx {}: {}", tx.hash, e))?
+                    {
+                        Some(receipt) if receipt.status() => TxState::Confirmed,
+                        Some(_) => TxState::Reverted,
+                        None => match provider
+                            .get_transaction_by_hash(hash)
+                            .await
+                            .map_err(|e| format!("Failed to check tx {}: {}", tx.hash, e))?
+                        {
+                            Some(_) => {
+                                report.still_pending.push(tx.hash.clone());
+                                continue;
+                            }
+                            None => TxState::Dropped,
+                        },
+                    }
+                }
+                TxState::Confirmed => TxState::Confirmed,
+                _ => continue,
+            };
+
+            let state = match state {
+                TxState::Confirmed => {
+                    let sent_at = chrono::DateTime::parse_from_rfc3339(&tx.sent_at)
+                        .map(|t| t.with_timezone(&chrono::Utc))
+                        .unwrap_or_else(|_| chrono::Utc::now());
+                    ledger::record_completion(
+                        app,
+                        LedgerEntry {
+                            script_id: record.script_id.clone(),
+                            address: item.address.clone(),
+                            task: item.step.clone(),
+                            day: ledger::ledger_day_at(sent_at, reset_minutes),
+                            tx_hash: Some(tx.hash.clone()),
+                            verified: None,
+                            completed_at: chrono::Utc::now().to_rfc3339(),
+                        },
+                    )?;
+                    report.confirmed += 1;
+                    log.info(&format!("{} {}: tx {} confirmed", item.address, item.step, tx.hash));
+                    TxState::Recorded
+                }
+                TxState::Reverted => {
+                    report.reverted += 1;
+                    log.warn(&format!("{} {}: tx {} reverted", item.address, item.step, tx.hash));
+                    TxState::Reverted
+                }
+                _ => {
+                    report.dropped += 1;
+                    log.warn(&format!("{} {}: tx {} dropped", item.address, item.step, tx.hash));
+                    TxState::Dropped
+                }
+            };
+            set_tx_state(app, &record.run_id, &tx.hash, state)?;
+        }
+    }
+    Ok(report)
+}
+
+/// 恢复中断（或已停止）的运行：先核对未确认的交易，再继续处理未完成的钱包
+pub async fn resume_run(app: tauri::AppHandle, run_id: &str) -> Result<String, String> {
+    if runner::is_run_active(run_id).await {
+        return Err(format!("Run {} is still active", run_id));
+    }
+    let record = load_run_record(&app, run_id)?;
+    if record.status == RunOutcome::Completed {
+        return Err(format!("Run {} already completed", run_id));
+    }
+    let script = super::registry::get_script(&record.script_id)?;
+
+    let report = reconcile_queue(&app, script.as_ref(), &record).await?;
+    if !report.still_pending.is_empty() {
+        return Err(format!(
+            "{} transaction(s) still pending, try again later: {}",
+            report.still_pending.len(),
+            report.still_pending.join(", ")
+        ));
+    }
+
+    // 强制运行不看台账，已完成的步骤只能从队列得知
+    let completed = completed_steps(&load_queue(&app, run_id)?);
+    runner::resume_run(app, record, completed).await
+}
+
+/// 未正常结束的运行队列
+fn unfinished_queues(app: &tauri::AppHandle) -> Result<Vec<WorkQueue>, String> {
+    let dir = queue_dir(app)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut queues = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| e.to_string())?.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        if let Some(queue) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<WorkQueue>(&c).ok())
+        {
+            if !queue.finished {
+                queues.push(queue);
+            }
+        }
+    }
+    Ok(queues)
+}
+
+/// 应用启动时（尚无运行）把上次未正常结束的运行标记为中断，返回标记的个数
+pub fn mark_interrupted_runs(app: &tauri::AppHandle) -> Result<usize, String> {
+    let mut marked = 0;
+    for queue in unfinished_queues(app)? {
+        if let Ok(mut record) = load_run_record(app, &queue.run_id) {
+            if record.status == RunOutcome::Running {
+                record.status = RunOutcome::Interrupted;
+                save_run_record(app, &record)?;
+                marked += 1;
+            }
+        }
+    }
+    Ok(marked)
+}
+
+fn interrupted_run(record: &RunRecord, queue: &WorkQueue) -> InterruptedRun {
+    InterruptedRun {
+        summary: record.summary(),
+        remaining_items: queue
+            .items
+            .iter()
+            .filter(|i| {
+                matches!(
+                    i.status,
+                    WorkStatus::Pending | WorkStatus::Running | WorkStatus::Cancelled
+                )
+            })
+            .count(),
+        pending_txs: queue
+            .items
+            .iter()
+            .flat_map(|i| &i.txs)
+            .filter(|t| !t.auxiliary && matches!(t.state, TxState::Pending | TxState::Confirmed))
+            .count(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_work_queue(app: tauri::AppHandle, run_id: String) -> Result<WorkQueue, String> {
+    load_queue(&app, &run_id)
+}
+
+/// 可恢复的中断运行（最新的在前）
+#[tauri::command]
+pub async fn list_interrupted_runs(
+    app: tauri::AppHandle,
+    script_id: Option<String>,
+) -> Result<Vec<InterruptedRun>, String> {
+    let mut runs = Vec::new();
+    for queue in unfinished_queues(&app)? {
+        if script_id.as_ref().is_some_and(|id| *id != queue.script_id)
+            || runner::is_run_active(&queue.run_id).await
+        {
+            continue;
+        }
+        if let Ok(mut record) = load_run_record(&app, &queue.run_id) {
+            if record.status == RunOutcome::Running {
+                record.status = RunOutcome::Interrupted;
+            }
+            runs.push(interrupted_run(&record, &queue));
+        }
+    }
+    runs.sort_by(|a, b| b.summary.started_at.cmp(&a.summary.started_at));
+    Ok(runs)
+}
+
+/// 恢复中断的运行，返回运行ID
+#[tauri::command]
+pub async fn resume_script_run(app: tauri::AppHandle, run_id: String) -> Result<String, String> {
+    resume_run(app, &run_id).await
+}
+
+/// 放弃恢复，运行保持中断状态
+#[tauri::command]
+pub async fn discard_interrupted_run(app: tauri::AppHandle, run_id: String) -> Result<(), String> {
+    if runner::is_run_active(&run_id).await {
+        return Err(format!("Run {} is still active", run_id));
+    }
+    close_queue(&app, &run_id)
+}
@@ -0,0 +1,21 @@
+use super::pharos::PharosScript;
+use super::types::*;
+use lazy_static::lazy_static;
+use std::sync::Arc;
+
+lazy_static! {
+    /// 已注册的脚本，按列表展示顺序
+    static ref SCRIPTS: Vec<Arc<dyn Script>> = vec![Arc::new(PharosScript)];
+}
+
+pub fn list() -> Vec<ScriptMeta> {
+    SCRIPTS.iter().map(|s| s.meta()).collect()
+}
+
+pub fn get_script(script_id: &str) -> Result<Arc<dyn Script>, String> {
+    SCRIPTS
+        .iter()
+        .find(|s| s.meta().id == script_id)
+        .cloned()
+        .ok_or_else(|| format!("Script not found: {}", script_id))
+}
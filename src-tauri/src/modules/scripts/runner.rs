@@ -0,0 +1,702 @@
+use super::captcha::RunCaptcha;
+use super::context::*;
+use super::history::*;
+use super::ledger::ledger_day;
+use super::logs::{close_run_log, prune_logs};
+use super::queue::{close_queue, open_queue, CompletedSteps};
+use super::registry;
+use super::types::*;
+use crate::modules::proxy::{
+    check_planned_mapping, create_wallet_client, detect_exit_ip, exit_ip_unchanged, flush_traffic,
+    load_exit_ip_history, load_proxies, record_exit_ip, ExitIpSource,
+};
+use crate::modules::wallet::utils::{decrypt_private_key, load_wallets};
+use crate::modules::wallet::{flush_cookies, WalletAccount};
+use alloy::signers::local::PrivateKeySigner;
+use lazy_static::lazy_static;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Mutex as AsyncMutex;
+
+/// 并发钱包数上限
+const MAX_THREADS: usize = 50;
+
+lazy_static! {
+    /// 各脚本的运行状态
+    static ref RUNS: AsyncMutex<HashMap<String, RunState>> = AsyncMutex::new(HashMap::new());
+}
+
+#[derive(Default)]
+struct RunState {
+    is_running: bool,
+    cancel: CancelToken,
+    run_id: Option<String>,
+    started_at: Option<String>,
+    seed: Option<u64>,
+    results: HashMap<String, ScriptWalletResult>,
+    /// 当前（或最近一次）运行的记录，每个钱包完成后写盘
+    record: Option<RunRecord>,
+}
+
+impl RunState {
+    fn finish(&mut self, app: &tauri::AppHandle) {
+        self.is_running = false;
+        if let Some(record) = self.record.as_mut() {
+            record.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            record.status = if self.cancel.is_cancelled() {
+                RunOutcome::Cancelled
+            } else {
+                RunOutcome::Completed
+            };
+            if let Err(e) = save_run_record(app, record) {
+                eprintln!("Failed to save run {}: {}", record.run_id, e);
+            }
+            // 没有可处理的钱包时不会建立队列
+            let _ = close_queue(app, &record.run_id);
+        }
+    }
+}
+
+/// 单个钱包的执行结果和过程记录
+pub struct WalletRun {
+    pub result: ScriptWalletResult,
+    pub steps: Vec<StepOutcome>,
+    pub points_before: Option<u64>,
+    pub points_after: Option<u64>,
+}
+
+/// 一次运行中所有钱包共享的参数
+pub struct RunParams {
+    pub run_id: Option<String>,
+    pub config: Value,
+    pub options: RunOptions,
+    pub seed: u64,
+    pub cancel: CancelToken,
+    /// 重试模式下要处理的钱包（小写地址）
+    pub retry: Option<HashSet<String>>,
+    /// 恢复运行时已处理完的钱包（小写地址），不再执行
+    pub finished: HashSet<String>,
+    /// 本次运行共享的打码服务
+    pub captcha: RunCaptcha,
+    /// 恢复运行时队列中已完成的步骤次数，这些次数不再执行（强制运行也是）
+    pub completed: CompletedSteps,
+}
+
+impl RunParams {
+    /// 单独执行一个钱包（不属于某次运行）
+    pub fn single(script_id: &str, config: Value) -> Self {
+        Self {
+            run_id: None,
+            config,
+            options: RunOptions::default(),
+            seed: rand::random(),
+            cancel: CancelToken::new(),
+            retry: None,
+            finished: HashSet::new(),
+            captcha: RunCaptcha::new(script_id, None, 1),
+            completed: CompletedSteps::new(),
+        }
+    }
+
+    /// 由种子和键派生的随机数发生器，与 worker 调度顺序无关
+    fn rng_for(&self, key: &str) -> StdRng {
+        let digest = Sha256::new()
+            .chain_update(self.seed.to_le_bytes())
+            .chain_update(key.to_lowercase().as_bytes())
+            .finalize();
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        StdRng::seed_from_u64(u64::from_le_bytes(bytes))
+    }
+}
+
+/// 重试的基准运行：成功钱包的记录和要重试的失败钱包（小写地址）
+fn retry_base(
+    app: &tauri::AppHandle,
+    state: &RunState,
+    script_id: &str,
+    retry_run: &str,
+) -> Result<(Vec<WalletRunRecord>, HashSet<String>), String> {
+    let previous = match state.record.as_ref() {
+        Some(record) if record.run_id == retry_run => record.clone(),
+        _ => load_run_record(app, retry_run)?,
+    };
+    if previous.script_id != script_id {
+        return Err(format!("Run {} belongs to {}", retry_run, previous.script_id));
+    }
+    let (succeeded, failed): (Vec<WalletRunRecord>, Vec<WalletRunRecord>) =
+        previous.wallets.into_iter().partition(|w| w.success);
+    if failed.is_empty() {
+        return Err(format!("No failed wallets in run {}", retry_run));
+    }
+    let failed = failed.iter().map(|w| w.address.to_lowercase()).collect();
+    Ok((succeeded, failed))
+}
+
+/// 保存运行记录并置为运行中
+fn begin_run(
+    app: &tauri::AppHandle,
+    state: &mut RunState,
+    record: RunRecord,
+    retry: Option<HashSet<String>>,
+    finished: HashSet<String>,
+    completed: CompletedSteps,
+) -> Result<Arc<RunParams>, String> {
+    save_run_record(app, &record)?;
+    if let Err(e) = prune_logs(app) {
+        eprintln!("Failed to prune script logs: {}", e);
+    }
+
+    state.is_running = true;
+    state.cancel = CancelToken::new();
+    state.run_id = Some(record.run_id.clone());
+    state.started_at = Some(record.started_at.clone());
+    state.seed = Some(record.seed);
+    state.results = record.results();
+    let params = Arc::new(RunParams {
+        run_id: Some(record.run_id.clone()),
+        config: record.config.clone(),
+        options: record.options.clone(),
+        seed: record.seed,
+        cancel: state.cancel.clone(),
+        retry,
+        finished,
+        captcha: RunCaptcha::new(
+            &record.script_id,
+            Some(&record.run_id),
+            record.options.threads.clamp(1, MAX_THREADS),
+        ),
+        completed,
+    });
+    state.record = Some(record);
+    Ok(params)
+}
+
+fn spawn_run(app: tauri::AppHandle, script: Arc<dyn Script>, params: Arc<RunParams>) {
+    tauri::async_runtime::spawn(async move {
+        let script_id = script.meta().id;
+        if let Err(e) = run_wallets(&app, script, params.clone()).await {
+            eprintln!("{} tasks error: {}", script_id, e);
+        }
+        params.captcha.stop();
+
+        if let Some(state) = RUNS.lock().await.get_mut(&script_id) {
+            state.finish(&app);
+        }
+        if let Some(run_id) = &params.run_id {
+            close_run_log(run_id);
+        }
+    });
+}
+
+/// 启动脚本，按 `options.threads` 并发处理选中的钱包，返回运行ID
+pub async fn start_run(
+    app: tauri::AppHandle,
+    script_id: &str,
+    config: Value,
+    options: RunOptions,
+) -> Result<String, String> {
+    let script = registry::get_script(script_id)?;
+    script.validate_config(&config)?;
+
+    let mut runs = RUNS.lock().await;
+    let state = runs.entry(script_id.to_string()).or_default();
+    if state.is_running {
+        return Err("Tasks are already running".to_string());
+    }
+
+    // 重试模式：只处理上次失败的钱包，成功钱包的记录原样带入本次运行
+    let (carried, retry) = match &options.selection.failed_in_run {
+        Some(retry_run) => {
+            let (succeeded, failed) = retry_base(&app, state, script_id, retry_run)?;
+            (succeeded, Some(failed))
+        }
+        None => (Vec::new(), None),
+    };
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let record = RunRecord {
+        run_id: run_id.clone(),
+        script_id: script_id.to_string(),
+        config,
+        options,
+        seed,
+        started_at: chrono::Utc::now().to_rfc3339(),
+        finished_at: None,
+        status: RunOutcome::Running,
+        wallets: carried,
+    };
+    let params = begin_run(&app, state, record, retry, HashSet::new(), CompletedSteps::new())?;
+    drop(runs);
+
+    spawn_run(app, script, params);
+    Ok(run_id)
+}
+
+/// 继续中断或停止的运行（同一运行ID、种子和钱包顺序），已处理完的钱包不再执行
+///
+/// 调用前应先核对队列中未确认的交易（见 `queue::resume_run`），
+/// `completed` 为队列中已完成的步骤次数，未处理完的钱包只执行剩余部分
+pub async fn resume_run(
+    app: tauri::AppHandle,
+    mut record: RunRecord,
+    completed: CompletedSteps,
+) -> Result<String, String> {
+    let script = registry::get_script(&record.script_id)?;
+    script.validate_config(&record.config)?;
+
+    let mut runs = RUNS.lock().await;
+    let state = runs.entry(record.script_id.clone()).or_default();
+    if state.is_running {
+        return Err("Tasks are already running".to_string());
+    }
+
+    let retry = match &record.options.selection.failed_in_run {
+        Some(retry_run) => Some(retry_base(&app, state, &record.script_id, retry_run)?.1),
+        None => None,
+    };
+
+    // 被停止的钱包（包括未开始的）重新处理
+    record.wallets.retain(|w| !w.cancelled);
+    let finished = record
+        .wallets
+        .iter()
+        .map(|w| w.address.to_lowercase())
+        .collect();
+    record.status = RunOutcome::Running;
+    record.finished_at = None;
+
+    let run_id = record.run_id.clone();
+    let params = begin_run(&app, state, record, retry, finished, completed)?;
+    drop(runs);
+
+    spawn_run(app, script, params);
+    Ok(run_id)
+}
+
+/// 发送停止信号：不再开始新钱包，进行中的请求、回执等待和延迟立即中断
+///
+/// 被中断的钱包记为已取消，已广播未确认的交易留在结果的 `pending_txs` 中
+pub async fn stop_run(script_id: &str) -> Result<(), String> {
+    registry::get_script(script_id)?;
+    if let Some(state) = RUNS.lock().await.get(script_id) {
+        state.cancel.cancel();
+    }
+    Ok(())
+}
+
+pub async fn run_status(script_id: &str) -> Result<ScriptStatus, String> {
+    registry::get_script(script_id)?;
+    let runs = RUNS.lock().await;
+    let state = runs.get(script_id);
+
+    Ok(ScriptStatus {
+        script_id: script_id.to_string(),
+        is_running: state.is_some_and(|s| s.is_running),
+        run_id: state.and_then(|s| s.run_id.clone()),
+        started_at: state.and_then(|s| s.started_at.clone()),
+        seed: state.and_then(|s| s.seed),
+        results: state.map(|s| s.results.clone()).unwrap_or_default(),
+    })
+}
+
+/// 脚本是否有运行中的任务
+pub async fn is_script_running(script_id: &str) -> bool {
+    RUNS.lock()
+        .await
+        .get(script_id)
+        .is_some_and(|s| s.is_running)
+}
+
+/// 运行是否仍在进行（用于识别应用中途退出留下的记录）
+pub async fn is_run_active(run_id: &str) -> bool {
+    RUNS.lock()
+        .await
+        .values()
+        .any(|s| s.is_running && s.run_id.as_deref() == Some(run_id))
+}
+
+async fn set_result(script_id: &str, address: &str, result: ScriptWalletResult) {
+    if let Some(state) = RUNS.lock().await.get_mut(script_id) {
+        state.results.insert(address.to_string(), result);
+    }
+}
+
+/// 记录钱包的最终结果并把运行记录写盘
+async fn finish_wallet(app: &tauri::AppHandle, script_id: &str, record: WalletRunRecord) {
+    let mut runs = RUNS.lock().await;
+    let state = match runs.get_mut(script_id) {
+        Some(state) => state,
+        None => return,
+    };
+
+    state.results.insert(record.address.clone(), record.result());
+    if let Some(run) = state.record.as_mut() {
+        run.upsert_wallet(record);
+        if let Err(e) = save_run_record(app, run) {
+            eprintln!("Failed to save run {}: {}", run.run_id, e);
+        }
+    }
+}
+
+fn wallet_record(address: &str, started_at: String, run: WalletRun) -> WalletRunRecord {
+    WalletRunRecord {
+        address: address.to_string(),
+        success: run.result.success,
+        cancelled: run.result.cancelled,
+        message: run.result.message,
+        started_at,
+        finished_at: chrono::Utc::now().to_rfc3339(),
+        points_before: run.points_before,
+        points_after: run.points_after,
+        steps: run.steps,
+        pending_txs: run.result.pending_txs,
+        data: run.result.data,
+    }
+}
+
+/// 提示会与其他钱包共用出口的钱包
+fn warn_planned_links(app: &tauri::AppHandle, log: &ScriptLogger, wallets: &[WalletAccount]) {
+    let (history, proxies) = match (load_exit_ip_history(app), load_proxies(app)) {
+        (Ok(h), Ok(p)) => (h, p),
+        _ => return,
+    };
+
+    let mapping: Vec<(String, String)> = wallets
+        .iter()
+        .filter_map(|w| {
+            proxies
+                .iter()
+                .find(|p| {
+                    p.wallet_bindings
+                        .iter()
+                        .any(|b| b.eq_ignore_ascii_case(&w.address))
+                })
+                .map(|p| (w.address.clone(), p.id.clone()))
+        })
+        .collect();
+
+    for warning in check_planned_mapping(&history, &proxies, &mapping) {
+        let msg = format!(
+            "Shared exit ({:?} {}) via proxy {} with: {}",
+            warning.kind,
+            warning.value,
+            warning.proxy_id,
+            warning.linked_wallets.join(", ")
+        );
+        log.for_address(&warning.wallet).warn(&msg);
+    }
+}
+
+/// 按选择条件筛选钱包，返回选中的钱包和未找到的指定地址
+fn select_wallets(
+    wallets: Vec<WalletAccount>,
+    selection: &WalletSelection,
+    retry: Option<&HashSet<String>>,
+) -> (Vec<WalletAccount>, Vec<String>) {
+    let missing = selection
+        .addresses
+        .iter()
+        .filter(|a| !wallets.iter().any(|w| w.address.eq_ignore_ascii_case(a)))
+        .cloned()
+        .collect();
+
+    let selected = wallets
+        .into_iter()
+        .filter(|w| {
+            selection.addresses.is_empty()
+                || selection
+                    .addresses
+                    .iter()
+                    .any(|a| a.eq_ignore_ascii_case(&w.address))
+        })
+        .filter(|w| {
+            selection.tags.is_empty()
+                || w
+                    .tags
+                    .iter()
+                    .any(|t| selection.tags.iter().any(|s| s.eq_ignore_ascii_case(t)))
+        })
+        .filter(|w| retry.is_none_or(|r| r.contains(&w.address.to_lowercase())))
+        .collect();
+
+    (selected, missing)
+}
+
+async fn run_wallets(
+    app: &tauri::AppHandle,
+    script: Arc<dyn Script>,
+    params: Arc<RunParams>,
+) -> Result<(), String> {
+    let script_id = script.meta().id;
+    let options = &params.options;
+    let log = ScriptLogger::new(app, script.as_ref(), "SYSTEM").with_run(params.run_id.as_deref());
+
+    let wallets = load_wallets(app).map_err(|e| {
+        let msg = format!("Failed to load wallets: {}", e);
+        log.error(&msg);
+        msg
+    })?;
+
+    if wallets.is_empty() {
+        log.error("No wallets found");
+        return Ok(());
+    }
+
+    let wallets = if options.selection.is_empty() {
+        wallets
+    } else {
+        let total = wallets.len();
+        let (selected, missing) =
+            select_wallets(wallets, &options.selection, params.retry.as_ref());
+        for address in missing {
+            log.warn(&format!("Selected wallet not found: {}", address));
+        }
+        log.info(&format!("Selected {} of {} wallet(s)", selected.len(), total));
+        selected
+    };
+    if wallets.is_empty() {
+        log.error("No wallets match the selection");
+        return Ok(());
+    }
+
+    warn_planned_links(app, &log, &wallets);
+
+    // 地址去重，每个钱包只会被一个 worker 取走
+    let mut seen = HashSet::new();
+    let mut order: Vec<WalletAccount> = wallets
+        .into_iter()
+        .filter(|w| seen.insert(w.address.to_lowercase()))
+        .collect();
+    if options.shuffle {
+        order.shuffle(&mut StdRng::seed_from_u64(params.seed));
+    }
+    let skipped = options.skip_first.min(order.len());
+    if skipped > 0 {
+        order.drain(..skipped);
+        log.info(&format!("Skipped first {} wallet(s)", skipped));
+    }
+    if !params.finished.is_empty() {
+        let before = order.len();
+        order.retain(|w| !params.finished.contains(&w.address.to_lowercase()));
+        log.info(&format!(
+            "Resuming run, {} wallet(s) already processed",
+            before - order.len()
+        ));
+    }
+    if order.is_empty() {
+        log.info("No wallets left to process");
+        return Ok(());
+    }
+
+    if let Some(run_id) = &params.run_id {
+        let addresses: Vec<String> = order.iter().map(|w| w.address.clone()).collect();
+        if let Err(e) = open_queue(app, run_id, &script_id, &addresses, &script.steps(&params.config)) {
+            log.warn(&format!("Failed to open work queue: {}", e));
+        }
+    }
+
+    let threads = options.threads.clamp(1, MAX_THREADS).min(order.len());
+    let queue = Arc::new(Mutex::new(VecDeque::from(order)));
+
+    log.info(&format!(
+        "Starting tasks with {} worker(s), seed {}...",
+        threads, params.seed
+    ));
+
+    let mut workers = tokio::task::JoinSet::new();
+    for _ in 0..threads {
+        let app = app.clone();
+        let script = script.clone();
+        let script_id = script_id.clone();
+        let params = params.clone();
+        let queue = queue.clone();
+
+        workers.spawn(async move {
+            let cancel = &params.cancel;
+            loop {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                let wallet = match queue.lock().unwrap().pop_front() {
+                    Some(w) => w,
+                    None => break,
+                };
+
+                set_result(&script_id, &wallet.address, ScriptWalletResult::running()).await;
+                let started_at = chrono::Utc::now().to_rfc3339();
+
+                let mut run = run_wallet(&app, script.as_ref(), &wallet.address, &params, None)
+                    .await
+                    .unwrap_or_else(|e| WalletRun {
+                        result: ScriptWalletResult::failed(e),
+                        steps: Vec::new(),
+                        points_before: None,
+                        points_after: None,
+                    });
+                if !run.result.success && cancel.is_cancelled() {
+                    run.result = run.result.into_cancelled();
+                }
+
+                finish_wallet(&app, &script_id, wallet_record(&wallet.address, started_at, run))
+                    .await;
+
+                if queue.lock().unwrap().is_empty() {
+                    break;
+                }
+                let delay = params
+                    .options
+                    .account_delay
+                    .sample(&mut params.rng_for(&format!("{}#account_delay", wallet.address)));
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = cancel.cancelled() => {}
+                }
+            }
+        });
+    }
+    while workers.join_next().await.is_some() {}
+
+    if params.cancel.is_cancelled() {
+        // 未开始的钱包也记为已取消，便于只重试未完成的钱包
+        let unstarted: Vec<WalletAccount> = queue.lock().unwrap().drain(..).collect();
+        for wallet in &unstarted {
+            let run = WalletRun {
+                result: ScriptWalletResult::failed("Not started").into_cancelled(),
+                steps: Vec::new(),
+                points_before: None,
+                points_after: None,
+            };
+            let record = wallet_record(&wallet.address, chrono::Utc::now().to_rfc3339(), run);
+            finish_wallet(app, &script_id, record).await;
+        }
+        log.info(&format!(
+            "Tasks stopped by user ({} wallet(s) not started)",
+            unstarted.len()
+        ));
+    }
+
+    if let Err(e) = flush_traffic(app) {
+        log.error(&format!("Failed to save traffic usage: {}", e));
+    }
+    if let Err(e) = flush_cookies(app) {
+        log.error(&format!("Failed to save cookies: {}", e));
+    }
+
+    log.success("All tasks completed");
+    Ok(())
+}
+
+/// 为单个钱包准备 HTTP 客户端和签名器并执行脚本
+///
+/// 准备失败（找不到钱包、解密失败等）返回 Err
+pub async fn run_wallet(
+    app: &tauri::AppHandle,
+    script: &dyn Script,
+    address: &str,
+    params: &RunParams,
+    proxy_override: Option<String>,
+) -> Result<WalletRun, String> {
+    let run_id = params.run_id.as_deref();
+    let log = ScriptLogger::new(app, script, address).with_run(run_id);
+
+    // 显式指定的代理优先于钱包绑定
+    let http = create_wallet_client(app, address, run_id, proxy_override)
+        .inspect_err(|e| log.error(e))?
+        .with_impersonation(script.impersonate_hosts());
+    if http.tags().proxy_id.is_some() {
+        log.info("Using bound proxy");
+    }
+    // 代理和会话都没变时出口IP沿用上次的观测，不再经计费代理重复检测
+    if exit_ip_unchanged(app, &http, address).unwrap_or(false) {
+        log.info("Proxy session unchanged, skipping exit IP check");
+    } else {
+        match params
+            .cancel
+            .run_until_cancelled(detect_exit_ip(&http))
+            .await
+            .map_err(String::from)?
+        {
+            Ok(ip) => {
+                log.info(&format!("Exit IP: {}", ip));
+                if let Err(e) =
+                    record_exit_ip(app, &http, address, &ip, ExitIpSource::Run).await
+                {
+                    log.warn(&format!("Failed to record exit IP: {}", e));
+                }
+            }
+            Err(e) => log.warn(&format!("Exit IP check failed: {}", e)),
+        }
+    }
+
+    log.info("Decrypting private key...");
+    let app_password = "unodrop_secure_password_2024";
+    let wallets = load_wallets(app)?;
+    let wallet = wallets
+        .iter()
+        .find(|w| w.address.eq_ignore_ascii_case(address))
+        .ok_or_else(|| {
+            let msg = "Wallet not found";
+            log.error(msg);
+            msg.to_string()
+        })?;
+
+    let private_key = decrypt_private_key(&wallet.encrypted_key, app_password)
+        .inspect_err(|e| log.error(&format!("Decryption failed: {}", e)))?;
+    log.add_secret(&private_key);
+    let signer = private_key
+        .trim_start_matches("0x")
+        .parse::<PrivateKeySigner>()
+        .map_err(|e| {
+            let msg = format!("Invalid private key: {}", e);
+            log.error(&msg);
+            msg
+        })?;
+
+    let ctx = ScriptContext {
+        app: app.clone(),
+        script_id: script.meta().id,
+        run_id: run_id.map(|s| s.to_string()),
+        address: address.to_string(),
+        config: params.config.clone(),
+        signer,
+        http,
+        log,
+        cancel: params.cancel.clone(),
+        captcha: params.captcha.clone(),
+        rng: Mutex::new(params.rng_for(address)),
+        step_delay: params.options.step_delay,
+        ledger_day: ledger_day(script.daily_reset_minutes(&params.config)),
+        force: params.options.force,
+        resumed: params
+            .completed
+            .get(&address.to_lowercase())
+            .cloned()
+            .unwrap_or_default(),
+        pending_txs: Mutex::new(Vec::new()),
+        steps: Mutex::new(Vec::new()),
+        points: Mutex::new((None, None)),
+    };
+
+    let mut result = script.run(&ctx).await;
+    result.pending_txs = ctx.take_pending_txs();
+    if !result.pending_txs.is_empty() {
+        ctx.log.warn(&format!(
+            "Broadcast without receipt: {}",
+            result.pending_txs.join(", ")
+        ));
+    }
+    let (points_before, points_after) = *ctx.points.lock().unwrap();
+    Ok(WalletRun {
+        result,
+        steps: ctx.take_steps(),
+        points_before,
+        points_after,
+    })
+}
@@ -0,0 +1,420 @@
+use super::context::ScriptLogger;
+use super::presets::preset_config;
+use super::registry;
+use super::runner;
+use super::types::*;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+use lazy_static::lazy_static;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+/// 调度检查间隔
+const SCHEDULER_TICK_SECS: u64 = 30;
+/// 到点后超过该时间才被检查到，视为错过（休眠或关机）
+const MISSED_GRACE_SECS: i64 = 120;
+/// 补跑的最长延迟，更早错过的运行直接跳过
+const CATCH_UP_WINDOW_HOURS: i64 = 24;
+
+lazy_static! {
+    /// 定时任务内存副本，首次使用时从磁盘加载，修改后写回
+    static ref JOBS: Mutex<Option<Vec<ScheduledJob>>> = Mutex::new(None);
+    /// 到点但因脚本正在运行而等待的任务
+    static ref WAITING: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+/// 调度规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleSpec {
+    /// 标准 5 段 cron 表达式（分 时 日 月 周），按任务时区解释
+    Cron { expression: String },
+    /// 每天 `time`（HH:MM），在 ±`jitter_minutes` 分钟内随机偏移
+    Daily {
+        time: String,
+        #[serde(default)]
+        jitter_minutes: u32,
+    },
+}
+
+impl ScheduleSpec {
+    /// `after` 之后的下一次运行时间
+    pub fn next_after(&self, tz: Tz, after: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+        match self {
+            ScheduleSpec::Cron { expression } => {
+                let cron = Cron::new(expression)
+                    .parse()
+                    .map_err(|e| format!("Invalid cron expression: {}", e))?;
+                cron.find_next_occurrence(&after.with_timezone(&tz), false)
+                    .map(|t| t.with_timezone(&Utc))
+                    .map_err(|e| format!("No upcoming time for {}: {}", expression, e))
+            }
+            ScheduleSpec::Daily {
+                time,
+                jitter_minutes,
+            } => {
+                let at = NaiveTime::parse_from_str(time, "%H:%M")
+                    .map_err(|_| format!("Invalid time (expected HH:MM): {}", time))?;
+                let jitter = ChronoDuration::minutes(*jitter_minutes as i64);
+
+                // 整个偏移窗口都在 `after` 之后的那一天才算下一次，避免同一天跑两次
+                let mut day = after.with_timezone(&tz).date_naive();
+                for _ in 0..3 {
+                    // 夏令时跳过的时刻没有对应的本地时间，顺延到下一天
+                    if let Some(base) = tz.from_local_datetime(&day.and_time(at)).earliest() {
+                        let base = base.with_timezone(&Utc);
+                        if base - jitter > after {
+                            let offset = match *jitter_minutes as i64 {
+                                0 => 0,
+                                j => rand::thread_rng().gen_range(-j * 60..=j * 60),
+                            };
+                            return Ok(base + ChronoDuration::seconds(offset));
+                        }
+                    }
+                    day = day.succ_opt().ok_or("Date out of range")?;
+                }
+                Err(format!("No upcoming time for {}", time))
+            }
+        }
+    }
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 定时运行的脚本任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    /// 为空时保存为新任务
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub script_id: String,
+    /// 使用的配置预设，为空时使用 `config`
+    #[serde(default)]
+    pub preset: Option<String>,
+    #[serde(default)]
+    pub config: Value,
+    /// 运行选项，包括钱包范围
+    #[serde(default)]
+    pub options: RunOptions,
+    pub schedule: ScheduleSpec,
+    /// IANA 时区，如 Asia/Shanghai
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 错过的运行（休眠或关机）在恢复后补跑一次
+    #[serde(default = "default_true")]
+    pub catch_up: bool,
+    #[serde(default)]
+    pub next_run_at: Option<String>,
+    #[serde(default)]
+    pub last_run_at: Option<String>,
+    #[serde(default)]
+    pub last_run_id: Option<String>,
+    /// 最近一次启动失败或错过的原因，成功启动后清空
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+impl ScheduledJob {
+    fn tz(&self) -> Result<Tz, String> {
+        self.timezone
+            .parse::<Tz>()
+            .map_err(|_| format!("Unknown timezone: {}", self.timezone))
+    }
+
+    /// 按当前设置计算 `after` 之后的下一次运行，停用时为空
+    fn reschedule(&mut self, after: DateTime<Utc>) -> Result<(), String> {
+        self.next_run_at = if self.enabled {
+            Some(self.schedule.next_after(self.tz()?, after)?.to_rfc3339())
+        } else {
+            None
+        };
+        Ok(())
+    }
+
+    fn config(&self, app: &tauri::AppHandle) -> Result<Value, String> {
+        match &self.preset {
+            Some(name) => preset_config(app, &self.script_id, name),
+            None if self.config.is_null() => Ok(Value::Object(Default::default())),
+            None => Ok(self.config.clone()),
+        }
+    }
+}
+
+/// `scheduled_job_run` 事件
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledJobEvent {
+    pub job_id: String,
+    pub run_id: Option<String>,
+    pub error: Option<String>,
+}
+
+fn parse_time(value: &Option<String>) -> Option<DateTime<Utc>> {
+    value
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn load_jobs_file(app: &tauri::AppHandle) -> Result<Vec<ScheduledJob>, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let jobs_path = app_data_dir.join("scheduled_jobs.json");
+
+    if !jobs_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&jobs_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+fn save_jobs_file(app: &tauri::AppHandle, jobs: &[ScheduledJob]) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&app_data_dir).map_err(|e| e.to_string())?;
+
+    let json = serde_json::to_string_pretty(jobs).map_err(|e| e.to_string())?;
+    fs::write(app_data_dir.join("scheduled_jobs.json"), json).map_err(|e| e.to_string())
+}
+
+fn with_jobs<R>(
+    app: &tauri::AppHandle,
+    f: impl FnOnce(&mut Vec<ScheduledJob>) -> R,
+) -> Result<R, String> {
+    let mut jobs = JOBS.lock().unwrap();
+    if jobs.is_none() {
+        *jobs = Some(load_jobs_file(app)?);
+    }
+    Ok(f(jobs.as_mut().unwrap()))
+}
+
+/// 修改单个任务并写盘
+fn update_job<R>(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    f: impl FnOnce(&mut ScheduledJob) -> Result<R, String>,
+) -> Result<R, String> {
+    with_jobs(app, |jobs| {
+        let job = jobs
+            .iter_mut()
+            .find(|j| j.id == job_id)
+            .ok_or_else(|| format!("Scheduled job not found: {}", job_id))?;
+        let result = f(job)?;
+        save_jobs_file(app, jobs)?;
+        Ok(result)
+    })?
+}
+
+async fn start_job(app: &tauri::AppHandle, job: &ScheduledJob) -> Result<String, String> {
+    let config = job.config(app)?;
+    runner::start_run(app.clone(), &job.script_id, config, job.options.clone()).await
+}
+
+fn log_job(app: &tauri::AppHandle, job: &ScheduledJob, message: &str, failed: bool) {
+    if let Ok(script) = registry::get_script(&job.script_id) {
+        let log = ScriptLogger::new(app, script.as_ref(), "SCHEDULER");
+        let message = format!("[{}] {}", job.name, message);
+        if failed {
+            log.warn(&message);
+        } else {
+            log.info(&message);
+        }
+    }
+}
+
+/// 启动到点的任务；同一脚本正在运行时等待其结束，不重叠执行
+async fn run_due_jobs(app: &tauri::AppHandle) -> Result<(), String> {
+    let now = Utc::now();
+    let due: Vec<(ScheduledJob, DateTime<Utc>)> = with_jobs(app, |jobs| {
+        jobs.iter()
+            .filter(|j| j.enabled)
+            .filter_map(|j| parse_time(&j.next_run_at).map(|t| (j.clone(), t)))
+            .filter(|(_, t)| *t <= now)
+            .collect()
+    })?;
+
+    for (job, scheduled) in due {
+        let late = now - scheduled;
+        let waiting = WAITING.lock().unwrap().contains(&job.id);
+        let missed = late > ChronoDuration::hours(CATCH_UP_WINDOW_HOURS)
+            || (!waiting && !job.catch_up && late > ChronoDuration::seconds(MISSED_GRACE_SECS));
+
+        let result = if missed {
+            Err(format!("Missed run scheduled at {}", scheduled.to_rfc3339()))
+        } else if runner::is_script_running(&job.script_id).await {
+            if WAITING.lock().unwrap().insert(job.id.clone()) {
+                log_job(app, &job, "Script is running, waiting for it to finish", false);
+            }
+            continue;
+        } else {
+            start_job(app, &job).await
+        };
+        WAITING.lock().unwrap().remove(&job.id);
+
+        match &result {
+            Ok(run_id) => log_job(app, &job, &format!("Started run {}", run_id), false),
+            Err(e) => log_job(app, &job, e, true),
+        }
+        let _ = app.emit(
+            "scheduled_job_run",
+            ScheduledJobEvent {
+                job_id: job.id.clone(),
+                run_id: result.as_ref().ok().cloned(),
+                error: result.as_ref().err().cloned(),
+            },
+        );
+
+        // 多次错过只补跑一次，下一次从现在往后算
+        let updated = update_job(app, &job.id, |job| {
+            match &result {
+                Ok(run_id) => {
+                    job.last_run_at = Some(now.to_rfc3339());
+                    job.last_run_id = Some(run_id.clone());
+                    job.last_error = None;
+                }
+                Err(e) => job.last_error = Some(e.clone()),
+            }
+            job.reschedule(now)
+        });
+        if let Err(e) = updated {
+            eprintln!("Failed to reschedule job {}: {}", job.id, e);
+        }
+    }
+    Ok(())
+}
+
+/// 后台调度：启动时立即检查一次（补跑关机期间错过的任务），之后定时检查
+pub fn spawn_scheduler(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_due_jobs(&app).await {
+                eprintln!("Scheduler error: {}", e);
+            }
+            tokio::time::sleep(Duration::from_secs(SCHEDULER_TICK_SECS)).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_scheduled_jobs(app: tauri::AppHandle) -> Result<Vec<ScheduledJob>, String> {
+    with_jobs(&app, |jobs| jobs.clone())
+}
+
+/// 保存定时任务（`id` 为空时新建），并重新计算下一次运行时间
+#[tauri::command]
+pub fn save_scheduled_job(
+    app: tauri::AppHandle,
+    mut job: ScheduledJob,
+) -> Result<ScheduledJob, String> {
+    job.name = job.name.trim().to_string();
+    if job.name.is_empty() {
+        return Err("Job name is required".to_string());
+    }
+    let script = registry::get_script(&job.script_id)?;
+    script.validate_config(&job.config(&app)?)?;
+    job.reschedule(Utc::now())?;
+
+    with_jobs(&app, |jobs| {
+        match jobs.iter_mut().find(|j| !job.id.is_empty() && j.id == job.id) {
+            Some(existing) => {
+                job.created_at = existing.created_at.clone();
+                job.last_run_at = existing.last_run_at.clone();
+                job.last_run_id = existing.last_run_id.clone();
+                job.last_error = existing.last_error.clone();
+                *existing = job.clone();
+            }
+            None => {
+                job.id = uuid::Uuid::new_v4().to_string();
+                job.created_at = Utc::now().to_rfc3339();
+                job.last_run_at = None;
+                job.last_run_id = None;
+                job.last_error = None;
+                jobs.push(job.clone());
+            }
+        }
+        save_jobs_file(&app, jobs)?;
+        Ok(job)
+    })?
+}
+
+#[tauri::command]
+pub fn delete_scheduled_job(app: tauri::AppHandle, job_id: String) -> Result<bool, String> {
+    WAITING.lock().unwrap().remove(&job_id);
+    with_jobs(&app, |jobs| {
+        let before = jobs.len();
+        jobs.retain(|j| j.id != job_id);
+        if jobs.len() == before {
+            return Ok(false);
+        }
+        save_jobs_file(&app, jobs)?;
+        Ok(true)
+    })?
+}
+
+/// 启用或停用定时任务，启用时从现在起计算下一次运行
+#[tauri::command]
+pub fn set_scheduled_job_enabled(
+    app: tauri::AppHandle,
+    job_id: String,
+    enabled: bool,
+) -> Result<ScheduledJob, String> {
+    WAITING.lock().unwrap().remove(&job_id);
+    update_job(&app, &job_id, |job| {
+        job.enabled = enabled;
+        job.reschedule(Utc::now())?;
+        Ok(job.clone())
+    })
+}
+
+/// 立即运行一次（不影响下一次定时运行），返回运行ID
+#[tauri::command]
+pub async fn run_scheduled_job_now(app: tauri::AppHandle, job_id: String) -> Result<String, String> {
+    let job = with_jobs(&app, |jobs| jobs.iter().find(|j| j.id == job_id).cloned())?
+        .ok_or_else(|| format!("Scheduled job not found: {}", job_id))?;
+    let run_id = start_job(&app, &job).await?;
+
+    update_job(&app, &job_id, |job| {
+        job.last_run_at = Some(Utc::now().to_rfc3339());
+        job.last_run_id = Some(run_id.clone());
+        job.last_error = None;
+        Ok(())
+    })?;
+    Ok(run_id)
+}
+
+/// 预览接下来 `count` 次运行时间（默认 5 次）
+#[tauri::command]
+pub fn preview_schedule(
+    schedule: ScheduleSpec,
+    timezone: Option<String>,
+    count: Option<usize>,
+) -> Result<Vec<String>, String> {
+    let timezone = timezone.unwrap_or_else(default_timezone);
+    let tz = timezone
+        .parse::<Tz>()
+        .map_err(|_| format!("Unknown timezone: {}", timezone))?;
+
+    let mut times = Vec::new();
+    let mut after = Utc::now();
+    for _ in 0..count.unwrap_or(5).min(50) {
+        after = schedule.next_after(tz, after)?;
+        times.push(after.with_timezone(&tz).to_rfc3339());
+    }
+    Ok(times)
+}
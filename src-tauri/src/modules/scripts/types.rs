@@ -0,0 +1,234 @@
+use super::context::ScriptContext;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// 脚本元信息（脚本列表展示用）
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptMeta {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub logo: String,
+    pub category: String,
+    pub website: String,
+    /// 配置项的 JSON Schema，前端据此生成配置表单
+    pub config_schema: Value,
+}
+
+/// 单个钱包的执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptWalletResult {
+    pub success: bool,
+    pub message: String,
+    /// 脚本自定义的附加数据（如登录得到的 JWT）
+    #[serde(default)]
+    pub data: Option<Value>,
+    /// 因停止信号中断
+    #[serde(default)]
+    pub cancelled: bool,
+    /// 已广播但未等到回执的交易哈希
+    #[serde(default)]
+    pub pending_txs: Vec<String>,
+}
+
+impl ScriptWalletResult {
+    pub fn running() -> Self {
+        Self::failed("Running...")
+    }
+
+    pub fn failed(message: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            message: message.into(),
+            data: None,
+            cancelled: false,
+            pending_txs: Vec::new(),
+        }
+    }
+
+    /// 标记为被停止中断（消息以 `Cancelled` 开头，旧页面据此识别）
+    pub fn into_cancelled(mut self) -> Self {
+        self.cancelled = true;
+        if !self.message.starts_with("Cancelled") {
+            self.message = format!("Cancelled: {}", self.message);
+        }
+        self
+    }
+}
+
+/// 随机延迟范围（毫秒）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DelayRange {
+    pub min_ms: u64,
+    pub max_ms: u64,
+}
+
+impl DelayRange {
+    pub const fn new(min_ms: u64, max_ms: u64) -> Self {
+        Self { min_ms, max_ms }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> Duration {
+        let (lo, hi) = (self.min_ms.min(self.max_ms), self.min_ms.max(self.max_ms));
+        Duration::from_millis(if lo == hi { lo } else { rng.gen_range(lo..=hi) })
+    }
+}
+
+/// 随机数值范围（如转账金额）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AmountRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl AmountRange {
+    pub const fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> f64 {
+        if self.max <= self.min {
+            self.min
+        } else {
+            rng.gen_range(self.min..self.max)
+        }
+    }
+}
+
+/// 要处理的钱包范围，各条件同时满足；都为空时处理全部钱包
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WalletSelection {
+    /// 指定地址
+    pub addresses: Vec<String>,
+    /// 钱包标签，带任一标签即可
+    pub tags: Vec<String>,
+    /// 只处理在该次运行中失败（或未完成）的钱包，其余钱包的结果沿用该次运行
+    pub failed_in_run: Option<String>,
+}
+
+impl WalletSelection {
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty() && self.tags.is_empty() && self.failed_in_run.is_none()
+    }
+}
+
+/// 运行选项（与脚本无关的框架设置）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunOptions {
+    /// 同时处理的钱包数
+    pub threads: usize,
+    /// 打乱钱包顺序
+    pub shuffle: bool,
+    /// 跳过排序后的前 N 个钱包（同一种子重跑时可从中断处继续）
+    pub skip_first: usize,
+    /// 同一 worker 处理完一个钱包后到下一个钱包前的延迟
+    pub account_delay: DelayRange,
+    /// 脚本内步骤之间的延迟
+    pub step_delay: DelayRange,
+    /// 随机种子，None 时随机生成；相同种子复现钱包顺序、延迟和随机金额
+    pub seed: Option<u64>,
+    /// 钱包范围
+    pub selection: WalletSelection,
+    /// 忽略任务台账，今日已完成的任务也重新执行
+    pub force: bool,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            threads: 1,
+            shuffle: false,
+            skip_first: 0,
+            account_delay: DelayRange::new(0, 0),
+            step_delay: DelayRange::new(1000, 2000),
+            seed: None,
+            selection: WalletSelection::default(),
+            force: false,
+        }
+    }
+}
+
+/// 脚本运行状态
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptStatus {
+    pub script_id: String,
+    pub is_running: bool,
+    pub run_id: Option<String>,
+    pub started_at: Option<String>,
+    /// 本次运行使用的随机种子
+    pub seed: Option<u64>,
+    pub results: HashMap<String, ScriptWalletResult>,
+}
+
+/// `script_log` 事件，同时按 JSON 行写入运行日志
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptLogEvent {
+    pub script_id: String,
+    #[serde(default)]
+    pub run_id: Option<String>,
+    pub address: String,
+    /// 产生日志时所在的步骤
+    #[serde(default)]
+    pub step: Option<String>,
+    pub message: String,
+    pub level: LogLevel,
+    pub timestamp: u64,
+}
+
+/// 日志级别，按严重程度排序（用于最低级别过滤）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Success,
+    Warn,
+    Error,
+}
+
+pub type ScriptFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// 脚本实现：框架负责钱包遍历、代理/客户端、签名器和停止信号，脚本只处理单个钱包
+pub trait Script: Send + Sync {
+    fn meta(&self) -> ScriptMeta;
+
+    /// 需要模拟浏览器握手的 API 域名
+    fn impersonate_hosts(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// 除 `script_log` 外额外发送的日志事件名（兼容旧页面）
+    fn legacy_log_event(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// 每日任务重置时间（UTC 零点后的分钟数），用于划分任务台账的项目日
+    fn daily_reset_minutes(&self, _config: &Value) -> u32 {
+        0
+    }
+
+    /// 启动前校验配置
+    fn validate_config(&self, _config: &Value) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// 计划执行的步骤，运行开始时为每个钱包建立工作队列项
+    fn steps(&self, _config: &Value) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// 交易所在链的 RPC，恢复中断的运行时用于核对未确认的交易
+    fn rpc_url(&self, _config: &Value) -> Option<&'static str> {
+        None
+    }
+
+    /// 处理单个钱包
+    fn run<'a>(&'a self, ctx: &'a ScriptContext) -> ScriptFuture<'a, ScriptWalletResult>;
+}
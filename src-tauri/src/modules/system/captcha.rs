@@ -0,0 +1,226 @@
+use super::settings::load_captcha_settings;
+use crate::common::captcha::{self, CaptchaProvider, CaptchaService, CaptchaSolveRecord};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+/// 打码记录落盘间隔
+const USAGE_FLUSH_INTERVAL_SECS: u64 = 30;
+
+lazy_static! {
+    /// captcha_usage.json 的读-改-写锁
+    static ref USAGE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// 读取已落盘的打码记录
+pub fn load_captcha_usage(app: &tauri::AppHandle) -> Result<Vec<CaptchaSolveRecord>, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let usage_path = app_data_dir.join("captcha_usage.json");
+
+    if !usage_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&usage_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_else(|_| Vec::new()))
+}
+
+fn save_captcha_usage(app: &tauri::AppHandle, records: &[CaptchaSolveRecord]) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&app_data_dir).map_err(|e| e.to_string())?;
+    let usage_path = app_data_dir.join("captcha_usage.json");
+
+    let json = serde_json::to_string_pretty(records).map_err(|e| e.to_string())?;
+    std::fs::write(&usage_path, json).map_err(|e| e.to_string())
+}
+
+/// 把内存中的打码记录追加到磁盘，失败时记录放回内存等下次落盘
+///
+/// 取出、读取、写回在同一把锁内完成，后台落盘和查询同时落盘时不会互相覆盖
+pub fn flush_captcha_usage(app: &tauri::AppHandle) -> Result<(), String> {
+    let _guard = USAGE_LOCK.lock().unwrap();
+    let pending = captcha::take_pending();
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let mut records = match load_captcha_usage(app) {
+        Ok(records) => records,
+        Err(e) => {
+            captcha::restore_pending(pending);
+            return Err(e);
+        }
+    };
+    let appended = records.len();
+    records.extend(pending);
+    save_captcha_usage(app, &records).inspect_err(|_| {
+        captcha::restore_pending(records.split_off(appended));
+    })
+}
+
+/// 启动后台落盘任务，并用已落盘的当日花费初始化预算
+pub fn spawn_captcha_usage_flush(app: tauri::AppHandle) {
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    if let Ok(records) = load_captcha_usage(&app) {
+        let spent = records
+            .iter()
+            .filter(|r| r.solved_at.starts_with(&today))
+            .map(|r| r.cost)
+            .sum();
+        captcha::seed_today(spent);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(USAGE_FLUSH_INTERVAL_SECS)).await;
+
+            if let Err(e) = flush_captcha_usage(&app) {
+                eprintln!("Captcha usage flush error: {}", e);
+            }
+        }
+    });
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptchaBalance {
+    pub provider: CaptchaProvider,
+    pub balance: Option<f64>,
+    pub error: Option<String>,
+}
+
+/// 查询记录的过滤条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CaptchaUsageFilter {
+    /// RFC3339 或 YYYY-MM-DD
+    pub since: Option<String>,
+    pub script: Option<String>,
+    pub wallet: Option<String>,
+    pub run_id: Option<String>,
+}
+
+/// 按平台和类型汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptchaUsageStat {
+    pub provider: CaptchaProvider,
+    pub kind: String,
+    pub solves: u64,
+    pub failures: u64,
+    pub cost: f64,
+    pub avg_duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptchaUsageSummary {
+    pub total_cost: f64,
+    pub spent_today: f64,
+    pub daily_budget: Option<f64>,
+    pub run_budget: Option<f64>,
+    pub stats: Vec<CaptchaUsageStat>,
+}
+
+fn filtered_usage(
+    app: &tauri::AppHandle,
+    filter: &CaptchaUsageFilter,
+) -> Result<Vec<CaptchaSolveRecord>, String> {
+    flush_captcha_usage(app)?;
+
+    let eq = |field: &Option<String>, wanted: &Option<String>| match wanted {
+        Some(w) => field.as_deref().is_some_and(|f| f.eq_ignore_ascii_case(w)),
+        None => true,
+    };
+
+    Ok(load_captcha_usage(app)?
+        .into_iter()
+        .filter(|r| filter.since.as_deref().is_none_or(|s| r.solved_at.as_str() >= s))
+        .filter(|r| eq(&r.script, &filter.script))
+        .filter(|r| eq(&r.wallet, &filter.wallet))
+        .filter(|r| eq(&r.run_id, &filter.run_id))
+        .collect())
+}
+
+/// 查询所有已启用平台的余额
+#[tauri::command]
+pub async fn get_captcha_balances(app: tauri::AppHandle) -> Result<Vec<CaptchaBalance>, String> {
+    let settings = load_captcha_settings(&app)?;
+    let service = CaptchaService::from_settings(&settings)?;
+
+    Ok(service
+        .balances()
+        .await
+        .into_iter()
+        .map(|(provider, result)| match result {
+            Ok(balance) => CaptchaBalance {
+                provider,
+                balance: Some(balance),
+                error: None,
+            },
+            Err(e) => CaptchaBalance {
+                provider,
+                balance: None,
+                error: Some(e.to_string()),
+            },
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn get_captcha_usage(
+    app: tauri::AppHandle,
+    filter: Option<CaptchaUsageFilter>,
+) -> Result<Vec<CaptchaSolveRecord>, String> {
+    filtered_usage(&app, &filter.unwrap_or_default())
+}
+
+/// 花费汇总（含当日已花费与预算）
+#[tauri::command]
+pub fn get_captcha_usage_summary(
+    app: tauri::AppHandle,
+    filter: Option<CaptchaUsageFilter>,
+) -> Result<CaptchaUsageSummary, String> {
+    let records = filtered_usage(&app, &filter.unwrap_or_default())?;
+    let settings = load_captcha_settings(&app)?;
+
+    let mut stats: Vec<CaptchaUsageStat> = Vec::new();
+    let mut durations: Vec<u64> = Vec::new();
+    for r in &records {
+        let idx = match stats
+            .iter()
+            .position(|s| s.provider == r.provider && s.kind == r.kind)
+        {
+            Some(i) => i,
+            None => {
+                stats.push(CaptchaUsageStat {
+                    provider: r.provider,
+                    kind: r.kind.clone(),
+                    solves: 0,
+                    failures: 0,
+                    cost: 0.0,
+                    avg_duration_ms: 0,
+                });
+                durations.push(0);
+                stats.len() - 1
+            }
+        };
+
+        let stat = &mut stats[idx];
+        stat.solves += 1;
+        if !r.success {
+            stat.failures += 1;
+        }
+        stat.cost += r.cost;
+        durations[idx] += r.duration_ms;
+    }
+    for (stat, total) in stats.iter_mut().zip(durations) {
+        stat.avg_duration_ms = total / stat.solves.max(1);
+    }
+
+    Ok(CaptchaUsageSummary {
+        total_cost: records.iter().map(|r| r.cost).sum(),
+        spent_today: captcha::spent_today(),
+        daily_budget: settings.daily_budget,
+        run_budget: settings.run_budget,
+        stats,
+    })
+}
@@ -1,6 +1,8 @@
 pub mod types;
 pub mod commands;
 pub mod settings;
+pub mod captcha;
 
 pub use commands::*;
 pub use settings::*;
+pub use captcha::*;
@@ -1,3 +1,7 @@
+use crate::common::captcha::{CaptchaService, CaptchaSettings};
+use crate::common::http::{set_default_retry_policy, RetryPolicy};
+use crate::common::rate_limit::{self, HostRateLimit};
+use crate::common::recorder::{self, RecorderStatus, RecordingInfo};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use tauri::Manager;
@@ -52,3 +56,126 @@ pub fn save_app_settings(app: tauri::AppHandle, settings: AppSettings) -> Result
     
     Ok(())
 }
+
+/// 网络请求设置：重试策略与按域名限速
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HttpSettings {
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    #[serde(default)]
+    pub host_limits: Vec<HostRateLimit>,
+}
+
+pub fn load_http_settings(app: &tauri::AppHandle) -> Result<HttpSettings, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings_path = app_data_dir.join("http_settings.json");
+    
+    if !settings_path.exists() {
+        return Ok(HttpSettings::default());
+    }
+    
+    let content = fs::read_to_string(&settings_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+/// 将网络请求设置应用到全局（启动时 / 保存后）
+pub fn apply_http_settings(settings: &HttpSettings) {
+    set_default_retry_policy(settings.retry.clone());
+    rate_limit::set_limits(settings.host_limits.clone());
+}
+
+#[tauri::command]
+pub fn get_http_settings(app: tauri::AppHandle) -> Result<HttpSettings, String> {
+    load_http_settings(&app)
+}
+
+#[tauri::command]
+pub fn save_http_settings(app: tauri::AppHandle, settings: HttpSettings) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&app_data_dir).map_err(|e| e.to_string())?;
+    
+    let settings_path = app_data_dir.join("http_settings.json");
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(&settings_path, json).map_err(|e| e.to_string())?;
+    
+    apply_http_settings(&settings);
+    
+    Ok(())
+}
+
+/// 当前生效的限速（含脚本注册的默认值）
+#[tauri::command]
+pub fn get_effective_rate_limits() -> Vec<HostRateLimit> {
+    rate_limit::current_limits()
+}
+
+pub fn load_captcha_settings(app: &tauri::AppHandle) -> Result<CaptchaSettings, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings_path = app_data_dir.join("captcha_settings.json");
+    
+    if !settings_path.exists() {
+        return Ok(CaptchaSettings::default());
+    }
+    
+    let content = fs::read_to_string(&settings_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+/// 按当前打码设置创建打码服务
+pub fn captcha_service(app: &tauri::AppHandle) -> Result<CaptchaService, String> {
+    let settings = load_captcha_settings(app)?;
+    Ok(CaptchaService::from_settings(&settings)?)
+}
+
+#[tauri::command]
+pub fn get_captcha_settings(app: tauri::AppHandle) -> Result<CaptchaSettings, String> {
+    load_captcha_settings(&app)
+}
+
+#[tauri::command]
+pub fn save_captcha_settings(app: tauri::AppHandle, settings: CaptchaSettings) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&app_data_dir).map_err(|e| e.to_string())?;
+    
+    let settings_path = app_data_dir.join("captcha_settings.json");
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(&settings_path, json).map_err(|e| e.to_string())
+}
+
+fn recordings_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(app_data_dir.join("http_recordings"))
+}
+
+/// 开始录制请求/响应（已脱敏），每次脚本运行写入一个录制文件
+#[tauri::command]
+pub fn start_http_recording(app: tauri::AppHandle) -> Result<(), String> {
+    recorder::start_recording(recordings_dir(&app)?)
+}
+
+/// 开始离线回放指定录制，返回录制条数
+#[tauri::command]
+pub fn start_http_replay(app: tauri::AppHandle, recording: String) -> Result<usize, String> {
+    recorder::start_replay(recordings_dir(&app)?, &recording)
+}
+
+/// 停止录制/回放；回放结束时 missed 和 unused 均为空说明运行与录制一致
+#[tauri::command]
+pub fn stop_http_recorder() -> RecorderStatus {
+    recorder::stop()
+}
+
+#[tauri::command]
+pub fn get_http_recorder_status() -> RecorderStatus {
+    recorder::status()
+}
+
+#[tauri::command]
+pub fn list_http_recordings(app: tauri::AppHandle) -> Result<Vec<RecordingInfo>, String> {
+    recorder::list_recordings(&recordings_dir(&app)?)
+}
+
+#[tauri::command]
+pub fn delete_http_recording(app: tauri::AppHandle, name: String) -> Result<bool, String> {
+    recorder::delete_recording(&recordings_dir(&app)?, &name)
+}
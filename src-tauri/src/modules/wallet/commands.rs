@@ -1,5 +1,9 @@
+use super::cookies::*;
 use super::types::*;
 use super::utils::*;
+use crate::common::cookies::{parse_json, parse_netscape, to_json, to_netscape, StoredCookie};
+use crate::common::fingerprint::BrowserProfile;
+use crate::modules::proxy::ProxyFallbackPolicy;
 use tauri::Manager;
 
 #[tauri::command]
@@ -87,6 +91,8 @@ pub async fn batch_import_private_keys(
             encrypted_key,
             encrypted_mnemonic: None,  // 导入的私钥没有助记词
             created_at: chrono::Utc::now().to_rfc3339(),
+            proxy_fallback: ProxyFallbackPolicy::default(),
+            tags: Vec::new(),
         };
         
         wallets.push(new_wallet);
@@ -206,6 +212,8 @@ pub async fn batch_import_wallets(
             encrypted_key,
             encrypted_mnemonic,
             created_at: imported_wallet.created_at.clone().unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+            proxy_fallback: ProxyFallbackPolicy::default(),
+            tags: Vec::new(),
         };
         
         wallets.push(new_wallet);
@@ -335,6 +343,8 @@ pub async fn batch_create_wallets(
             encrypted_key,
             encrypted_mnemonic: Some(encrypted_mnemonic),
             created_at: chrono::Utc::now().to_rfc3339(),
+            proxy_fallback: ProxyFallbackPolicy::default(),
+            tags: Vec::new(),
         };
         
         wallets.push(new_wallet);
@@ -523,5 +533,182 @@ pub async fn delete_wallet(app: tauri::AppHandle, address: String) -> Result<boo
     let json = serde_json::to_string_pretty(&wallets).map_err(|e| e.to_string())?;
     std::fs::write(&wallets_path, json).map_err(|e| e.to_string())?;
     
+    // 清理指纹
+    remove_fingerprint(&app, &address)?;
+
+    // 清理 cookie
+    if wallet_cookie_jar(&app, &address)?.remove_domain(None) > 0 {
+        flush_cookies(&app)?;
+    }
+    
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn update_wallet_proxy_fallback(
+    app: tauri::AppHandle,
+    address: String,
+    policy: ProxyFallbackPolicy,
+) -> Result<bool, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let wallets_path = data_dir.join("wallets.json");
+    
+    if !wallets_path.exists() {
+        return Ok(false);
+    }
+    
+    let content = std::fs::read_to_string(&wallets_path).map_err(|e| e.to_string())?;
+    let mut wallets: Vec<WalletAccount> = serde_json::from_str(&content).unwrap_or_else(|_| Vec::new());
+    
+    let wallet = match wallets.iter_mut().find(|w| w.address.eq_ignore_ascii_case(&address)) {
+        Some(w) => w,
+        None => return Ok(false),
+    };
+    wallet.proxy_fallback = policy;
+    
+    let json = serde_json::to_string_pretty(&wallets).map_err(|e| e.to_string())?;
+    std::fs::write(&wallets_path, json).map_err(|e| e.to_string())?;
+    
     Ok(true)
-}
\ No newline at end of file
+}
+
+/// 批量设置钱包标签（覆盖原有标签），返回更新的钱包数
+#[tauri::command]
+pub async fn update_wallet_tags(
+    app: tauri::AppHandle,
+    addresses: Vec<String>,
+    tags: Vec<String>,
+) -> Result<usize, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let wallets_path = data_dir.join("wallets.json");
+    
+    if !wallets_path.exists() {
+        return Ok(0);
+    }
+    
+    let content = std::fs::read_to_string(&wallets_path).map_err(|e| e.to_string())?;
+    let mut wallets: Vec<WalletAccount> = serde_json::from_str(&content).unwrap_or_else(|_| Vec::new());
+    
+    let mut tags: Vec<String> = tags
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    
+    let mut updated = 0;
+    for wallet in wallets
+        .iter_mut()
+        .filter(|w| addresses.iter().any(|a| a.eq_ignore_ascii_case(&w.address)))
+    {
+        wallet.tags = tags.clone();
+        updated += 1;
+    }
+    
+    if updated > 0 {
+        let json = serde_json::to_string_pretty(&wallets).map_err(|e| e.to_string())?;
+        std::fs::write(&wallets_path, json).map_err(|e| e.to_string())?;
+    }
+    
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn get_wallet_fingerprint(
+    app: tauri::AppHandle,
+    address: String,
+) -> Result<BrowserProfile, String> {
+    wallet_fingerprint(&app, &address)
+}
+
+/// 重新生成钱包指纹（之后该钱包的所有请求使用新指纹）
+#[tauri::command]
+pub async fn regenerate_wallet_fingerprint(
+    app: tauri::AppHandle,
+    address: String,
+) -> Result<BrowserProfile, String> {
+    regenerate_fingerprint(&app, &address)
+}
+
+/// 查看钱包保存的 cookie，可按域名过滤（含子域名）
+#[tauri::command]
+pub async fn get_wallet_cookies(
+    app: tauri::AppHandle,
+    address: String,
+    domain: Option<String>,
+) -> Result<Vec<StoredCookie>, String> {
+    let jar = wallet_cookie_jar(&app, &address)?;
+    Ok(filter_domain(jar.snapshot(), domain.as_deref()))
+}
+
+/// 导入 cookie（如从浏览器导出的登录态），同名同域同路径的会被覆盖，返回导入条数
+#[tauri::command]
+pub async fn import_wallet_cookies(
+    app: tauri::AppHandle,
+    address: String,
+    format: CookieFormat,
+    content: String,
+) -> Result<usize, String> {
+    let parsed = match format {
+        CookieFormat::Netscape => parse_netscape(&content),
+        CookieFormat::Json => parse_json(&content)?,
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let valid: Vec<StoredCookie> = parsed.into_iter().filter(|c| !c.is_expired(now)).collect();
+    let count = valid.len();
+
+    let jar = wallet_cookie_jar(&app, &address)?;
+    for cookie in valid {
+        jar.upsert(cookie);
+    }
+    flush_cookies(&app)?;
+
+    Ok(count)
+}
+
+/// 导出钱包 cookie，可按域名过滤
+#[tauri::command]
+pub async fn export_wallet_cookies(
+    app: tauri::AppHandle,
+    address: String,
+    format: CookieFormat,
+    domain: Option<String>,
+) -> Result<String, String> {
+    let jar = wallet_cookie_jar(&app, &address)?;
+    let cookies = filter_domain(jar.snapshot(), domain.as_deref());
+
+    match format {
+        CookieFormat::Netscape => Ok(to_netscape(&cookies)),
+        CookieFormat::Json => to_json(&cookies),
+    }
+}
+
+/// 清除钱包 cookie，domain 为空时全部清除，返回清除条数
+#[tauri::command]
+pub async fn clear_wallet_cookies(
+    app: tauri::AppHandle,
+    address: String,
+    domain: Option<String>,
+) -> Result<usize, String> {
+    let jar = wallet_cookie_jar(&app, &address)?;
+    let removed = jar.remove_domain(domain.as_deref());
+    flush_cookies(&app)?;
+
+    Ok(removed)
+}
+
+fn filter_domain(cookies: Vec<StoredCookie>, domain: Option<&str>) -> Vec<StoredCookie> {
+    match domain {
+        Some(d) => {
+            let d = d.trim_start_matches('.').to_lowercase();
+            let suffix = format!(".{}", d);
+            cookies
+                .into_iter()
+                .filter(|c| c.domain == d || c.domain.ends_with(&suffix))
+                .collect()
+        }
+        None => cookies,
+    }
+}
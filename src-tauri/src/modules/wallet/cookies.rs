@@ -0,0 +1,135 @@
+use super::utils::{decrypt_data, encrypt_data};
+use crate::common::cookies::{self, CookieJar, StoredCookie};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Manager;
+
+/// cookie 落盘间隔
+const COOKIE_FLUSH_INTERVAL_SECS: u64 = 30;
+
+lazy_static! {
+    /// wallet_cookies.json 读写锁，取修改、读取、写回在同一把锁内完成
+    static ref COOKIE_FILE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// 钱包(小写地址) -> 域名 -> 混淆后的 cookie 列表
+///
+/// 与私钥存储相同的 XOR 混淆（固定口令），只避免文件内容直接可读，不是加密
+type CookieFile = HashMap<String, HashMap<String, String>>;
+
+fn load_cookie_file(app: &tauri::AppHandle) -> Result<CookieFile, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let cookies_path = data_dir.join("wallet_cookies.json");
+
+    if !cookies_path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content = std::fs::read_to_string(&cookies_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_else(|_| HashMap::new()))
+}
+
+fn save_cookie_file(app: &tauri::AppHandle, file: &CookieFile) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    let cookies_path = data_dir.join("wallet_cookies.json");
+
+    let json = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+    std::fs::write(&cookies_path, json).map_err(|e| e.to_string())
+}
+
+/// 读取钱包已保存的 cookie（还原失败的域名跳过）
+pub fn load_wallet_cookies(
+    app: &tauri::AppHandle,
+    address: &str,
+) -> Result<Vec<StoredCookie>, String> {
+    let app_password = "unodrop_secure_password_2024";
+    let file = {
+        let _guard = COOKIE_FILE_LOCK.lock().unwrap();
+        load_cookie_file(app)?
+    };
+
+    let domains = match file.get(&address.to_lowercase()) {
+        Some(d) => d,
+        None => return Ok(Vec::new()),
+    };
+
+    Ok(domains
+        .values()
+        .filter_map(|encrypted| decrypt_data(encrypted, app_password).ok())
+        .filter_map(|json| serde_json::from_str::<Vec<StoredCookie>>(&json).ok())
+        .flatten()
+        .collect())
+}
+
+/// 获取钱包的 cookie 罐，首次使用时从磁盘加载
+pub fn wallet_cookie_jar(app: &tauri::AppHandle, address: &str) -> Result<Arc<CookieJar>, String> {
+    if let Some(jar) = cookies::jar_for(address) {
+        return Ok(jar);
+    }
+
+    let stored = load_wallet_cookies(app, address)?;
+    Ok(cookies::register_jar(address, stored))
+}
+
+/// 把有变化的 cookie 罐写入磁盘（过期 cookie 不落盘）
+///
+/// 写入失败时恢复修改标记，下次落盘重试
+pub fn flush_cookies(app: &tauri::AppHandle) -> Result<(), String> {
+    let _guard = COOKIE_FILE_LOCK.lock().unwrap();
+    let dirty = cookies::take_dirty_jars();
+    if dirty.is_empty() {
+        return Ok(());
+    }
+
+    let wallets: Vec<String> = dirty.iter().map(|(w, _)| w.clone()).collect();
+    let result = write_dirty_jars(app, dirty);
+    if result.is_err() {
+        cookies::mark_dirty(&wallets);
+    }
+    result
+}
+
+fn write_dirty_jars(
+    app: &tauri::AppHandle,
+    dirty: Vec<(String, Vec<StoredCookie>)>,
+) -> Result<(), String> {
+    let app_password = "unodrop_secure_password_2024";
+    let mut file = load_cookie_file(app)?;
+
+    for (wallet, stored) in dirty {
+        if stored.is_empty() {
+            file.remove(&wallet);
+            continue;
+        }
+
+        let mut by_domain: HashMap<String, Vec<StoredCookie>> = HashMap::new();
+        for cookie in stored {
+            by_domain.entry(cookie.domain.clone()).or_default().push(cookie);
+        }
+
+        let mut domains = HashMap::new();
+        for (domain, list) in by_domain {
+            let json = serde_json::to_string(&list).map_err(|e| e.to_string())?;
+            domains.insert(domain, encrypt_data(&json, app_password));
+        }
+        file.insert(wallet, domains);
+    }
+
+    save_cookie_file(app, &file)
+}
+
+/// 启动后台 cookie 落盘任务
+pub fn spawn_cookie_flush(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(COOKIE_FLUSH_INTERVAL_SECS)).await;
+
+            if let Err(e) = flush_cookies(&app) {
+                eprintln!("Cookie flush error: {}", e);
+            }
+        }
+    });
+}
@@ -1,7 +1,9 @@
 pub mod types;
 pub mod commands;
 pub mod utils;
+pub mod cookies;
 
 pub use types::*;
 pub use commands::*;
 pub use utils::*;
+pub use cookies::*;
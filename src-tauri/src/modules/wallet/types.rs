@@ -1,3 +1,4 @@
+use crate::modules::proxy::ProxyFallbackPolicy;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -7,6 +8,12 @@ pub struct WalletAccount {
     pub encrypted_key: String,
     pub encrypted_mnemonic: Option<String>,
     pub created_at: String,
+    /// 绑定代理不可用时的备用策略
+    #[serde(default)]
+    pub proxy_fallback: ProxyFallbackPolicy,
+    /// 分组标签，启动脚本时可按标签选择钱包
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -43,3 +50,13 @@ pub struct ExportedWallet {
     pub mnemonic: Option<String>,
     pub created_at: Option<String>,
 }
+
+/// cookie 导入导出格式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CookieFormat {
+    /// curl / 浏览器扩展使用的 cookies.txt
+    Netscape,
+    /// Cookie-Editor 等扩展导出的 JSON 数组
+    Json,
+}
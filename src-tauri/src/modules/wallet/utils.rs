@@ -1,4 +1,15 @@
+use super::types::WalletAccount;
+use crate::common::fingerprint::BrowserProfile;
+use lazy_static::lazy_static;
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::Manager;
+
+lazy_static! {
+    /// 钱包指纹内存副本（键为小写地址），首次使用时从磁盘加载，修改后在锁内写回
+    static ref FINGERPRINTS: Mutex<Option<HashMap<String, BrowserProfile>>> = Mutex::new(None);
+}
 
 /// 简单的 XOR 加密（实际生产应使用 AES-GCM）
 pub fn encrypt_data(data: &str, password: &str) -> String {
@@ -58,3 +69,93 @@ pub fn validate_and_get_address(private_key_str: &str) -> Result<String, String>
 
     Ok(format!("{:?}", signer.address()))
 }
+
+/// 读取钱包列表
+pub fn load_wallets(app: &tauri::AppHandle) -> Result<Vec<WalletAccount>, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let wallets_path = data_dir.join("wallets.json");
+
+    if !wallets_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&wallets_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_else(|_| Vec::new()))
+}
+
+fn load_fingerprints(
+    app: &tauri::AppHandle,
+) -> Result<HashMap<String, BrowserProfile>, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let fingerprints_path = data_dir.join("wallet_fingerprints.json");
+
+    if !fingerprints_path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content = std::fs::read_to_string(&fingerprints_path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_else(|_| HashMap::new()))
+}
+
+fn save_fingerprints(
+    app: &tauri::AppHandle,
+    fingerprints: &HashMap<String, BrowserProfile>,
+) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    let fingerprints_path = data_dir.join("wallet_fingerprints.json");
+
+    let json = serde_json::to_string_pretty(fingerprints).map_err(|e| e.to_string())?;
+    std::fs::write(&fingerprints_path, json).map_err(|e| e.to_string())
+}
+
+fn with_fingerprints<R>(
+    app: &tauri::AppHandle,
+    f: impl FnOnce(&mut HashMap<String, BrowserProfile>) -> Result<R, String>,
+) -> Result<R, String> {
+    let mut fingerprints = FINGERPRINTS.lock().unwrap();
+    if fingerprints.is_none() {
+        *fingerprints = Some(load_fingerprints(app)?);
+    }
+    f(fingerprints.as_mut().unwrap())
+}
+
+/// 获取钱包指纹，首次使用时生成并保存
+pub fn wallet_fingerprint(app: &tauri::AppHandle, address: &str) -> Result<BrowserProfile, String> {
+    let key = address.to_lowercase();
+
+    with_fingerprints(app, |fingerprints| {
+        if let Some(profile) = fingerprints.get(&key) {
+            return Ok(profile.clone());
+        }
+
+        let profile = BrowserProfile::generate();
+        fingerprints.insert(key, profile.clone());
+        save_fingerprints(app, fingerprints)?;
+
+        Ok(profile)
+    })
+}
+
+/// 重新生成钱包指纹并保存
+pub fn regenerate_fingerprint(
+    app: &tauri::AppHandle,
+    address: &str,
+) -> Result<BrowserProfile, String> {
+    with_fingerprints(app, |fingerprints| {
+        let profile = BrowserProfile::generate();
+        fingerprints.insert(address.to_lowercase(), profile.clone());
+        save_fingerprints(app, fingerprints)?;
+        Ok(profile)
+    })
+}
+
+/// 删除钱包指纹
+pub fn remove_fingerprint(app: &tauri::AppHandle, address: &str) -> Result<(), String> {
+    with_fingerprints(app, |fingerprints| {
+        if fingerprints.remove(&address.to_lowercase()).is_some() {
+            save_fingerprints(app, fingerprints)?;
+        }
+        Ok(())
+    })
+}
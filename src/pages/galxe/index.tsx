@@ -17,6 +17,9 @@ import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from '@
 import { Tabs, TabsContent, TabsList, TabsTrigger } from '@/components/ui/tabs';
 import { Separator } from '@/components/ui/separator';
 import { toast } from 'sonner';
+import { invoke } from '@tauri-apps/api/core';
+import { save } from '@tauri-apps/plugin-dialog';
+import { writeTextFile } from '@tauri-apps/plugin-fs';
 
 // --- Types (Kept same) ---
 interface Campaign { id: string; name: string; }
@@ -80,6 +83,41 @@ export function GalxePage() {
     toast.success('Configuration saved successfully');
   };
 
+  const handleExportLogs = async () => {
+    try {
+      // 优先导出后端持久化的日志，没有时导出页面内的日志
+      let content = await invoke<string>('export_script_logs', {
+        filter: { script_id: 'galxe' },
+        format: 'text',
+      });
+      if (!content) {
+        content = logs
+          .map(log => `[${new Date(log.timestamp).toISOString()}] ${log.type.toUpperCase()} ${log.message}`)
+          .join('\n');
+      }
+      if (!content) {
+        toast.info('No logs to export');
+        return;
+      }
+
+      const filePath = await save({
+        filters: [{ name: 'Log', extensions: ['log', 'txt'] }],
+        defaultPath: `galxe_logs_${new Date().toISOString().split('T')[0]}.log`,
+      });
+      if (!filePath) {
+        return;
+      }
+
+      await writeTextFile(filePath, content);
+      toast.success('Logs exported');
+    } catch (error) {
+      console.error('Export logs failed:', error);
+      toast.error('Failed to export logs', {
+        description: error instanceof Error ? error.message : String(error),
+      });
+    }
+  };
+
   const addLog = (message: string, type: LogEvent['type'] = 'info') => {
     setLogs(prev => [...prev, { timestamp: Date.now(), message, type }]);
   };
@@ -122,7 +160,7 @@ export function GalxePage() {
         <div className="flex items-center gap-3">
           {activeTab === 'monitor' && (
             <div className="flex items-center gap-1 mr-2">
-               <Button variant="ghost" size="icon" className="h-8 w-8 text-muted-foreground" title="Export Logs">
+               <Button variant="ghost" size="icon" className="h-8 w-8 text-muted-foreground" title="Export Logs" onClick={handleExportLogs}>
                  <Download className="w-4 h-4" />
                </Button>
             </div>
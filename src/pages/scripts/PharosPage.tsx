@@ -1,5 +1,5 @@
 import { useState, useEffect, useRef } from 'react';
-import { Play, Loader2, CheckCircle, XCircle, Terminal, Activity, Wallet, Clock, AlertCircle } from 'lucide-react';
+import { Play, RotateCcw, Loader2, CheckCircle, XCircle, Ban, Terminal, Activity, Wallet, Clock, AlertCircle } from 'lucide-react';
 import { Button } from '@/components/ui/button';
 import { Badge } from '@/components/ui/badge';
 import { invoke } from '@tauri-apps/api/core';
@@ -13,7 +13,7 @@ interface WalletAccount {
 
 interface TaskLog {
   address: string;
-  status: 'pending' | 'running' | 'success' | 'failed';
+  status: 'pending' | 'running' | 'success' | 'failed' | 'cancelled';
   message: string;
   jwt?: string;
   startTime?: number;
@@ -29,9 +29,22 @@ interface LogEvent {
 
 interface PharosStatusResponse {
     is_running: boolean;
+    run_id: string | null;
     results: Record<string, any>;
 }
 
+interface InterruptedRun {
+    summary: {
+        run_id: string;
+        started_at: string;
+        total: number;
+        succeeded: number;
+        failed: number;
+    };
+    remaining_items: number;
+    pending_txs: number;
+}
+
 import { Input } from '@/components/ui/input';
 
 export function PharosPage() {
@@ -40,14 +53,20 @@ export function PharosPage() {
   const [isRunning, setIsRunning] = useState(false);
   const [realtimeLogs, setRealtimeLogs] = useState<LogEvent[]>([]);
   const [inviteCode, setInviteCode] = useState('S6NGMzXSCDBxhnwo');
+  const [threadCount, setThreadCount] = useState(1);
+  const [lastRunId, setLastRunId] = useState<string | null>(null);
+  const [interruptedRun, setInterruptedRun] = useState<InterruptedRun | null>(null);
+  const [isResuming, setIsResuming] = useState(false);
   const logsEndRef = useRef<HTMLDivElement>(null);
 
   const processedCount = Object.values(taskStates).filter(t => t.status !== 'pending').length;
   const totalCount = wallets.length;
+  const failedCount = Object.values(taskStates).filter(t => t.status === 'failed' || t.status === 'cancelled').length;
 
   useEffect(() => {
     loadWallets();
     syncStatus();
+    loadInterruptedRun();
     
     const savedCode = localStorage.getItem('pharos_invite_code');
     if (savedCode) {
@@ -84,6 +103,7 @@ export function PharosPage() {
     try {
         const status = await invoke<PharosStatusResponse>('get_pharos_status');
         setIsRunning(status.is_running);
+        setLastRunId(status.run_id);
         
         // Update task states based on backend results
         setTaskStates(prev => {
@@ -92,7 +112,10 @@ export function PharosPage() {
                 if (next[address]) {
                     next[address] = {
                         ...next[address],
-                        status: result.success ? 'success' : (result.message === 'Running...' ? 'running' : 'failed'),
+                        status: result.success ? 'success'
+                            : result.message === 'Running...' ? 'running'
+                            : result.message.startsWith('Cancelled') ? 'cancelled'
+                            : 'failed',
                         message: result.message,
                         jwt: result.jwt
                     };
@@ -105,6 +128,44 @@ export function PharosPage() {
     }
   };
 
+  // Runs left unfinished by a crash or restart can be resumed from the persisted work queue
+  const loadInterruptedRun = async () => {
+    try {
+        const runs = await invoke<InterruptedRun[]>('list_interrupted_runs', { scriptId: 'pharos' });
+        setInterruptedRun(runs[0] ?? null);
+    } catch (e) {
+        console.error("Failed to load interrupted runs", e);
+    }
+  };
+
+  const handleResume = async () => {
+    if (!interruptedRun) return;
+    try {
+        setIsResuming(true);
+        // Pending transactions are checked on chain before any step is retried
+        await invoke('resume_script_run', { runId: interruptedRun.summary.run_id });
+        setInterruptedRun(null);
+        setIsRunning(true);
+        setRealtimeLogs([]);
+        toast.success('Run resumed');
+        syncStatus();
+    } catch (e: any) {
+        toast.error('Failed to resume: ' + e.toString());
+    } finally {
+        setIsResuming(false);
+    }
+  };
+
+  const handleDiscardInterrupted = async () => {
+    if (!interruptedRun) return;
+    try {
+        await invoke('discard_interrupted_run', { runId: interruptedRun.summary.run_id });
+        loadInterruptedRun();
+    } catch (e: any) {
+        toast.error('Failed to discard run: ' + e.toString());
+    }
+  };
+
   const addLog = (address: string, message: string, level: string, timestamp: number) => {
     setRealtimeLogs(prev => [...prev, {
         address,
@@ -157,7 +218,7 @@ export function PharosPage() {
             return next;
         });
         
-        await invoke('start_pharos_tasks', { inviteCode });
+        await invoke('start_pharos_tasks', { inviteCode, threads: threadCount });
         localStorage.setItem('pharos_invite_code', inviteCode);
         toast.success('Tasks started in background');
     } catch (e: any) {
@@ -166,6 +227,34 @@ export function PharosPage() {
     }
   };
 
+  // Re-run only the wallets that failed in the last run; successful results are kept
+  const handleRetryFailed = async () => {
+    if (!lastRunId) return;
+    try {
+        setIsRunning(true);
+        setRealtimeLogs([]);
+        setTaskStates(prev => {
+            const next = { ...prev };
+            Object.keys(next).forEach(key => {
+                if (next[key].status === 'failed' || next[key].status === 'cancelled') {
+                    next[key].status = 'pending';
+                    next[key].message = 'Pending...';
+                }
+            });
+            return next;
+        });
+
+        await invoke('start_pharos_tasks', {
+            inviteCode,
+            options: { threads: threadCount, selection: { failed_in_run: lastRunId } },
+        });
+        toast.success(`Retrying ${failedCount} failed wallet(s)`);
+    } catch (e: any) {
+        setIsRunning(false);
+        toast.error('Failed to retry: ' + e.toString());
+    }
+  };
+
   const handleStop = async () => {
     try {
         await invoke('stop_pharos_tasks');
@@ -187,6 +276,7 @@ export function PharosPage() {
       case 'running': return 'text-blue-500';
       case 'success': return 'text-green-500';
       case 'failed': return 'text-red-500';
+      case 'cancelled': return 'text-yellow-500';
       default: return 'text-muted-foreground';
     }
   };
@@ -196,6 +286,7 @@ export function PharosPage() {
       case 'running': return <Loader2 className="w-4 h-4 animate-spin" />;
       case 'success': return <CheckCircle className="w-4 h-4" />;
       case 'failed': return <XCircle className="w-4 h-4" />;
+      case 'cancelled': return <Ban className="w-4 h-4" />;
       default: return <Clock className="w-4 h-4" />;
     }
   };
@@ -224,6 +315,18 @@ export function PharosPage() {
                     disabled={isRunning}
                 />
             </div>
+            <div className="flex items-center gap-2">
+                <span className="text-sm text-gray-400 whitespace-nowrap">Threads:</span>
+                <Input
+                    type="number"
+                    min={1}
+                    max={50}
+                    value={threadCount}
+                    onChange={(e) => setThreadCount(Math.max(1, Number(e.target.value)))}
+                    className="w-20 h-9 bg-black/20 border-white/10 text-white text-sm font-mono"
+                    disabled={isRunning}
+                />
+            </div>
             <div className="flex items-center gap-2 text-sm text-muted-foreground bg-accent/50 px-3 py-1.5 rounded-lg border border-white/5">
                 <Wallet className="w-4 h-4" />
                 <span>{wallets.length} Wallets</span>
@@ -234,6 +337,16 @@ export function PharosPage() {
                     <span>Processed: {processedCount} / {totalCount}</span>
                 </div>
             )}
+            {!isRunning && failedCount > 0 && lastRunId && (
+                <Button
+                    onClick={handleRetryFailed}
+                    variant="outline"
+                    className="border-red-500/30 text-red-400 hover:bg-red-500/10"
+                >
+                    <RotateCcw className="mr-2 h-4 w-4" />
+                    Retry Failed ({failedCount})
+                </Button>
+            )}
             <Button 
                 onClick={isRunning ? handleStop : handleStart} 
                 disabled={wallets.length === 0}
@@ -255,6 +368,29 @@ export function PharosPage() {
         </div>
       </div>
 
+      {interruptedRun && !isRunning && (
+        <div className="flex items-center justify-between px-6 py-3 border-b border-yellow-500/20 bg-yellow-500/10 shrink-0">
+          <div className="flex items-center gap-2 text-sm text-yellow-400">
+            <AlertCircle className="w-4 h-4" />
+            <span>
+              Interrupted run from {new Date(interruptedRun.summary.started_at).toLocaleString()}:
+              {' '}{interruptedRun.remaining_items} unfinished step(s)
+              {interruptedRun.pending_txs > 0 && `, ${interruptedRun.pending_txs} unconfirmed tx(s)`}
+            </span>
+          </div>
+          <div className="flex items-center gap-2">
+            <Button size="sm" variant="ghost" onClick={handleDiscardInterrupted} disabled={isResuming}>
+              Discard
+            </Button>
+            <Button size="sm" variant="outline" onClick={handleResume} disabled={isResuming}
+              className="border-yellow-500/30 text-yellow-400 hover:bg-yellow-500/10">
+              {isResuming ? <Loader2 className="mr-2 h-4 w-4 animate-spin" /> : <RotateCcw className="mr-2 h-4 w-4" />}
+              Resume
+            </Button>
+          </div>
+        </div>
+      )}
+
       {/* Main Content Split View */}
       <div className="flex-1 flex overflow-hidden min-h-0">
         {/* Left: Wallet List */}
@@ -1,4 +1,4 @@
-import { useState } from 'react';
+import { useEffect, useState } from 'react';
 import {
   ExternalLink,
   Settings2,
@@ -8,49 +8,52 @@ import {
   Terminal
 } from 'lucide-react';
 import { useNavigate } from 'react-router-dom';
+import { invoke } from '@tauri-apps/api/core';
 import { Button } from '@/components/ui/button';
 
-interface ScriptProject {
+interface ScriptMeta {
   id: string;
   name: string;
   logo: string;
   description: string;
-  status: 'active' | 'inactive';
   website: string;
   category: string;
 }
 
+interface ScriptStatus {
+  is_running: boolean;
+}
+
+interface ScriptProject extends ScriptMeta {
+  status: 'active' | 'inactive';
+}
+
 export function ScriptPage() {
   const navigate = useNavigate();
 
-  // 脚本项目列表
-  const [projects] = useState<ScriptProject[]>([
-    {
-      id: 'pharos',
-      name: 'Pharos Testnet',
-      logo: '💡',
-      description: '自动化执行 Pharos 测试网任务',
-      status: 'inactive',
-      website: 'https://pharos.xyz',
-      category: '自动化脚本'
-    },
-    // {
-    //   id: 'yom',
-    //   name: 'YOM',
-    //   logo: '🪙',
-    //   description: '自动化执行 YOM 相关任务',
-    //   status: 'inactive',
-    //   website: 'https://yom.io',
-    //   category: '自动化脚本'
-    // }
-  ]);
+  // 脚本项目列表（后端注册的脚本）
+  const [projects, setProjects] = useState<ScriptProject[]>([]);
+
+  useEffect(() => {
+    const loadProjects = async () => {
+      try {
+        const scripts = await invoke<ScriptMeta[]>('list_scripts');
+        const list = await Promise.all(
+          scripts.map(async (script) => {
+            const status = await invoke<ScriptStatus>('script_status', { scriptId: script.id });
+            return { ...script, status: status.is_running ? 'active' : 'inactive' } as ScriptProject;
+          })
+        );
+        setProjects(list);
+      } catch (error) {
+        console.error('Failed to load scripts:', error);
+      }
+    };
+    loadProjects();
+  }, []);
 
   const handleToggleProject = (projectId: string) => {
-    if (projectId === 'pharos') {
-      navigate('/scripts/pharos');
-      return;
-    }
-    console.log('Toggle project:', projectId);
+    navigate(`/scripts/${projectId}`);
   };
 
   const handleConfigProject = (projectId: string) => {
@@ -129,7 +132,7 @@ export function ScriptPage() {
                     className="flex-1 bg-primary hover:bg-primary/90 text-white"
                   >
                     <Play className="w-3.5 h-3.5 mr-1.5" />
-                    {project.id === 'pharos' ? '打开' : '启动'}
+                    打开
                   </Button>
                 )}
                 <Button